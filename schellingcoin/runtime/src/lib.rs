@@ -18,7 +18,7 @@ use runtime_primitives::{
 };
 use client::{
 	block_builder::api::{CheckInherentsResult, InherentData, self as block_builder_api},
-	runtime_api, impl_runtime_apis
+	runtime_api, impl_runtime_apis, decl_runtime_apis
 };
 use version::RuntimeVersion;
 #[cfg(feature = "std")]
@@ -33,6 +33,7 @@ pub use balances::Call as BalancesCall;
 pub use runtime_primitives::{Permill, Perbill};
 pub use timestamp::BlockPeriod;
 pub use support::{StorageValue, construct_runtime};
+pub use schelling::{DepositStats, EpochPhase, EpochState, FeedConfig, TopicId};
 
 /// The type that is used for identifying authorities.
 pub type AuthorityId = <AuthoritySignature as Verify>::Signer;
@@ -55,6 +56,9 @@ pub type BlockNumber = u64;
 /// Index of an account's extrinsic in the chain.
 pub type Nonce = u64;
 
+/// Balance of the token used for deposits and rewards of the schelling module.
+pub type TokenBalance = u128;
+
 /// Used for the module schelling in `./schelling.rs`
 mod schelling;
 mod token;
@@ -191,11 +195,13 @@ impl sudo::Trait for Runtime {
 /// Used for the module schelling in `./schelling.rs`
 impl schelling::Trait for Runtime {
 	type Event = Event;
+	type Signature = AccountSignature;
+	type OnNewValue = ();
 }
 
 impl token::Trait for Runtime {
 	type Event = Event;
-	type TokenBalance = u128;
+	type TokenBalance = TokenBalance;
 }
 
 construct_runtime!(
@@ -212,7 +218,7 @@ construct_runtime!(
 		Balances: balances,
 		Sudo: sudo,
 		// Used for the module schelling in `./schelling.rs`
-		SchellingModule: schelling::{Module, Call, Storage, Event<T>},
+		SchellingModule: schelling::{Module, Call, Storage, Config<T>, Event<T>},
 		TokenModule: token::{Module, Call, Storage, Event<T>},
 	}
 );
@@ -231,9 +237,57 @@ pub type BlockId = generic::BlockId<Block>;
 pub type UncheckedExtrinsic = generic::UncheckedMortalCompactExtrinsic<Address, Nonce, Call, AccountSignature>;
 /// Extrinsic type that has already been checked.
 pub type CheckedExtrinsic = generic::CheckedExtrinsic<AccountId, Nonce, Call>;
+/// Validity error of a commitment or reveal refused by the transaction pool, see `precheck_submission`.
+const INVALID_SUBMISSION: i8 = -1;
 /// Executive: handles dispatch to the various modules.
 pub type Executive = executive::Executive<Runtime, Block, Context, Balances, AllModules>;
 
+decl_runtime_apis! {
+	/// Queries into the state of the schelling oracle.
+	pub trait SchellingApi {
+		/// Total, min, max and average deposit locked in the current epoch.
+		fn deposit_stats() -> DepositStats<TokenBalance>;
+		/// Value the current epoch would resolve to with `value` revealed with `deposit` on top of the current reveals.
+		fn simulate_with(value: i64, deposit: TokenBalance) -> i64;
+		/// Decimals and unit to render the values of the feed.
+		fn feed_config() -> FeedConfig;
+		/// Blocks left until the rewards of the current epoch can be sent out, zero once they can.
+		fn blocks_until_resolution() -> BlockNumber;
+		/// Lowest and highest value rewarded if the epoch resolved over the current reveals.
+		fn projected_reward_band() -> (i64, i64);
+		/// Whether the account revealed a value in the current epoch of the topic.
+		fn has_revealed(topic: TopicId, account: AccountId) -> bool;
+		/// Block the current value was set in.
+		fn value_set_block() -> BlockNumber;
+		/// Phase, participation, locked deposits and blocks left of the current epoch.
+		fn epoch_state() -> EpochState<BlockNumber, TokenBalance>;
+		/// A page of the accounts penalized in the last settled epoch.
+		fn last_slashed(page: u32, page_size: u32) -> Vec<AccountId>;
+		/// Smallest deposit that moves the weighted median of the current reveals to `target`.
+		fn required_stake_for(target: i64) -> TokenBalance;
+		/// Current phase of the epoch and the blocks left in it.
+		fn epoch_phase() -> (EpochPhase, BlockNumber);
+		/// Minimal deposit of a submission.
+		fn min_deposit() -> TokenBalance;
+		/// A page of the winner counts of the last resolved epochs, oldest first.
+		fn winner_history(page: u32, page_size: u32) -> Vec<(BlockNumber, u32)>;
+		/// Up to `limit` of the sorted values of the last resolution, from position `start` on.
+		fn last_sorted_values(start: u32, limit: u32) -> Vec<i64>;
+		/// Whether the resolution block of the current epoch passed without the epoch being settled.
+		fn resolution_overdue() -> bool;
+		/// Number of resolved epochs kept in the value history.
+		fn history_len() -> u32;
+		/// Value last resolved for the topic.
+		fn topic_value(topic: TopicId) -> i64;
+		/// Up to `limit` accounts from position `start` on with the number of epochs they were rewarded in, at most 256.
+		fn reputation_page(start: u32, limit: u32) -> Vec<(AccountId, u32)>;
+		/// Number of messages settling the current epoch of the topic goes through, revealed values and commitments.
+		fn participant_count(topic: TopicId) -> u32;
+		/// Weight of settling the current epoch of the topic with its current participants.
+		fn send_rewards_weight(topic: TopicId) -> u64;
+	}
+}
+
 // Implement our runtime API endpoints. This is just a bunch of proxying.
 impl_runtime_apis! {
 	impl runtime_api::Core<Block> for Runtime {
@@ -284,6 +338,15 @@ impl_runtime_apis! {
 
 	impl runtime_api::TaggedTransactionQueue<Block> for Runtime {
 		fn validate_transaction(tx: <Block as BlockT>::Extrinsic) -> TransactionValidity {
+			// commitments and reveals failing their cheap checks are refused before they are charged
+			if let (Some((address, _, _, _)), Call::SchellingModule(call)) = (&tx.signature, &tx.function) {
+				if let Ok(who) = Indices::lookup(address.clone()) {
+					if SchellingModule::precheck_submission(call, &who).is_err() {
+						return TransactionValidity::Invalid(INVALID_SUBMISSION);
+					}
+				}
+			}
+
 			Executive::validate_transaction(tx)
 		}
 	}
@@ -305,4 +368,86 @@ impl_runtime_apis! {
 			Consensus::authorities()
 		}
 	}
+
+	impl self::SchellingApi<Block> for Runtime {
+		fn deposit_stats() -> DepositStats<TokenBalance> {
+			SchellingModule::deposit_stats()
+		}
+
+		fn simulate_with(value: i64, deposit: TokenBalance) -> i64 {
+			SchellingModule::simulate_with(value, deposit)
+		}
+
+		fn feed_config() -> FeedConfig {
+			SchellingModule::feed_config()
+		}
+
+		fn blocks_until_resolution() -> BlockNumber {
+			SchellingModule::blocks_until_resolution()
+		}
+
+		fn projected_reward_band() -> (i64, i64) {
+			SchellingModule::projected_reward_band()
+		}
+
+		fn has_revealed(topic: TopicId, account: AccountId) -> bool {
+			SchellingModule::has_revealed(topic, account)
+		}
+
+		fn value_set_block() -> BlockNumber {
+			SchellingModule::value_set_block()
+		}
+
+		fn epoch_state() -> EpochState<BlockNumber, TokenBalance> {
+			SchellingModule::epoch_state()
+		}
+
+		fn last_slashed(page: u32, page_size: u32) -> Vec<AccountId> {
+			SchellingModule::last_slashed_page(page, page_size)
+		}
+
+		fn required_stake_for(target: i64) -> TokenBalance {
+			SchellingModule::required_stake_for(target)
+		}
+
+		fn epoch_phase() -> (EpochPhase, BlockNumber) {
+			SchellingModule::epoch_phase()
+		}
+
+		fn min_deposit() -> TokenBalance {
+			SchellingModule::min_deposit()
+		}
+
+		fn winner_history(page: u32, page_size: u32) -> Vec<(BlockNumber, u32)> {
+			SchellingModule::winner_history_page(page, page_size)
+		}
+
+		fn last_sorted_values(start: u32, limit: u32) -> Vec<i64> {
+			SchellingModule::last_sorted_values_from(start, limit)
+		}
+
+		fn resolution_overdue() -> bool {
+			SchellingModule::resolution_overdue()
+		}
+
+		fn history_len() -> u32 {
+			SchellingModule::history_len()
+		}
+
+		fn topic_value(topic: TopicId) -> i64 {
+			SchellingModule::value(topic)
+		}
+
+		fn reputation_page(start: u32, limit: u32) -> Vec<(AccountId, u32)> {
+			SchellingModule::reputation_page(start, limit)
+		}
+
+		fn participant_count(topic: TopicId) -> u32 {
+			SchellingModule::participant_count(topic)
+		}
+
+		fn send_rewards_weight(topic: TopicId) -> u64 {
+			SchellingModule::send_rewards_weight(SchellingModule::participant_count(topic))
+		}
+	}
 }
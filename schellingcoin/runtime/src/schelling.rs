@@ -1,48 +1,595 @@
-use support::{decl_module, decl_storage, decl_event, ensure, StorageMap, StorageValue, dispatch::Result};
+use support::{decl_module, decl_storage, decl_event, ensure, StorageMap, StorageDoubleMap, StorageValue, Parameter, dispatch::Result};
 use parity_codec::{Decode, Encode};
-use runtime_primitives::traits::Hash;
+use runtime_primitives::traits::{Hash, Verify};
+use runtime_primitives::Perbill;
 use system::{ensure_signed, ensure_root};
-use runtime_primitives::traits::{CheckedAdd, CheckedSub, CheckedMul, CheckedDiv, As};
+use runtime_primitives::traits::{CheckedAdd, CheckedSub, CheckedMul, CheckedDiv, Saturating, Zero, As};
 use rstd::prelude::Vec;
+use rstd::result;
 
 use crate::token;
 
+/// Maximal number of values in a composite feed
+pub const MAX_FEED_WIDTH: u32 = 16;
+
+/// Maximal number of commitments relayed in one transaction
+pub const MAX_RELAYED_COMMITS: usize = 64;
+
+/// Maximal length of the unit label of a feed
+pub const MAX_UNIT_LEN: usize = 16;
+
+/// Maximal number of reward tiers
+pub const MAX_REWARD_TIERS: usize = 8;
+
+/// Maximal number of registered value consumers
+pub const MAX_CONSUMERS: usize = 16;
+
+/// Maximal number of epochs a deposit can be locked for
+pub const MAX_LOCK_DURATION: u32 = 32;
+
+/// Maximal number of slashed accounts kept from the last epoch
+pub const MAX_LAST_SLASHED: usize = 256;
+
+/// Maximal number of sorted values kept from the last resolution
+pub const MAX_LAST_SORTED_VALUES: usize = 1024;
+
+/// Maximal number of topics created besides the default one
+pub const MAX_TOPICS: usize = 16;
+
+/// Maximal number of delegates allowed to approve the reveal of a commitment
+pub const MAX_REVEAL_DELEGATES: usize = 8;
+
+/// Maximal number of accounts returned by one page of reputations
+pub const MAX_REPUTATION_PAGE: u32 = 256;
+
+/// Maximal number of assets paid to every winner besides its reward
+pub const MAX_REWARD_BUNDLES: usize = 8;
+
+/// Weight of a storage read, the unit the weights of the calls are given in
+pub const READ_WEIGHT: u64 = 25;
+
+/// Weight of a storage write
+pub const WRITE_WEIGHT: u64 = 100;
+
+/// Weight of a comparison of the values sorted by a resolution
+pub const COMPARE_WEIGHT: u64 = 1;
+
+/// Weight of submit_hash, the token lock and the config snapshot included
+pub const SUBMIT_HASH_WEIGHT: u64 = 14 * READ_WEIGHT + 6 * WRITE_WEIGHT;
+
+/// Weight of submit_value, the value counts of the epoch included
+pub const SUBMIT_VALUE_WEIGHT: u64 = 12 * READ_WEIGHT + 7 * WRITE_WEIGHT;
+
+/// Weight of withdraw, the token unlock and the forfeit included
+pub const WITHDRAW_WEIGHT: u64 = 9 * READ_WEIGHT + 5 * WRITE_WEIGHT;
+
+/// Weight of send_rewards whatever the number of participants
+pub const SEND_REWARDS_BASE_WEIGHT: u64 = 30 * READ_WEIGHT + 10 * WRITE_WEIGHT;
+
+/// Weight send_rewards adds for every participant, the token unlock and reward transfer included
+pub const SEND_REWARDS_PARTICIPANT_WEIGHT: u64 = 6 * READ_WEIGHT + 8 * WRITE_WEIGHT;
+
+/// Identifies an oracle question resolved independently of the others, e.g. a price pair
+pub type TopicId = u32;
+
+/// Topic holding the state from before topics were introduced, the records of the last resolution follow it
+pub const DEFAULT_TOPIC: TopicId = 0;
+
+/// Called for every registered consumer once a new value is resolved
+pub trait OnNewValue<AccountId> {
+	fn on_new_value(consumer: &AccountId, value: i64);
+
+	/// The signed value resolved by a fixed point feed
+	fn on_new_fixed_value(_consumer: &AccountId, _value: i128) {}
+}
+
+impl<AccountId> OnNewValue<AccountId> for () {
+	fn on_new_value(_consumer: &AccountId, _value: i64) {}
+}
+
 /// The module's configuration trait.
 pub trait Trait: system::Trait + token::Trait {
 	type Event: From<Event<Self>> + Into<<Self as system::Trait>::Event>;
+	/// Signature of a relayed commitment by the committing account
+	type Signature: Parameter + Verify<Signer = <Self as system::Trait>::AccountId>;
+	/// Notifies the registered consumers of a resolved value
+	type OnNewValue: OnNewValue<Self::AccountId>;
 }
 
 #[cfg_attr(feature = "std", derive(Debug))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
 
-pub struct Message<AccountId, Hash, TokenBalance> {
+pub struct Message<AccountId, Hash, TokenBalance, BlockNumber> {
 	owner: AccountId,
 	status: u32, 
 	hash: Hash, 
-	value: u64,
+	value: i64,
+	// all the positions of a composite feed, empty for a single value feed
+	values: Vec<i64>,
+	// signed value of a fixed point feed scaled by 10^decimals, zero for the other feeds
+	fixed: i128,
 	deposit: TokenBalance,
+	// start of the epoch the message was committed in
+	epoch: BlockNumber,
+	// block the value was revealed in
+	revealed_at: BlockNumber,
+	// epochs the deposit stays locked for, the current one included
+	lock_duration: u32,
+	// hash of the off-chain source data backing the value, zero if none was attached
+	evidence: Hash,
+	// accounts allowed to approve the reveal on behalf of the owner, none if only the owner reveals
+	delegates: Vec<AccountId>,
+	// approvals of distinct delegates that reveal the committed value
+	threshold: u32,
+	// delegates that approved the reveal so far
+	approvals: Vec<AccountId>,
+}
+
+/// Describes how to render the values of the feed, e.g. 2 decimals in "USD"
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct FeedConfig {
+	pub decimals: u8,
+	pub unit: Vec<u8>,
+}
+
+/// A band around the median rewarded with a multiple of the reward
+///
+/// `edge_bps` is the central share of the sorted submissions covered by the tier, counted
+/// from the median, and includes the inner tiers. Submissions outside of all the tiers are penalized.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RewardTier {
+	pub edge_bps: u32,
+	pub multiplier_bps: u32,
+}
+
+/// Economic parameters of the oracle set at once with `configure`
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct SchellingConfig<TokenBalance, BlockNumber> {
+	pub min_deposit: TokenBalance,
+	pub min_total_stake: TokenBalance,
+	/// Lengths of the hash and value submission rounds, pending until the next epoch during one
+	pub hash_submission_blocks: BlockNumber,
+	pub value_submission_blocks: BlockNumber,
+	pub reward_per_submission: TokenBalance,
+	pub fixed_pool_reward: TokenBalance,
+	pub percentile_mode: PercentileMode,
+	pub reward_tiers: Vec<RewardTier>,
+	pub weighted_rewards: bool,
+	pub penalty_recycle_bps: u32,
+	pub stake_signal_mode: bool,
+}
+
+/// Resolution parameters of an epoch, fixed once its first commitment is made
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq)]
+pub struct EpochConfig<TokenBalance> {
+	pub percentile_mode: PercentileMode,
+	pub reward_tiers: Vec<RewardTier>,
+	pub weighted_rewards: bool,
+	pub penalty_recycle_bps: u32,
+	pub median_tolerance: u64,
+	pub consolation_tolerance: u64,
+	pub weighted_mode: bool,
+	pub reward_per_submission: TokenBalance,
+	pub fixed_pool_reward: TokenBalance,
+	/// Amounts of the assets besides the main token paid to every winner
+	pub reward_bundles: Vec<(token::AssetId, TokenBalance)>,
+}
+
+/// Running accuracy of a reporter over the epochs it was resolved in
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Accuracy {
+	pub epochs: u32,
+	/// Average absolute distance of the revealed values from the resolved medians, rounded down
+	pub average_distance: u64,
+}
+
+/// Distribution of the deposits locked in the current epoch
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct DepositStats<TokenBalance> {
+	pub count: u32,
+	pub total: TokenBalance,
+	pub min: TokenBalance,
+	pub max: TokenBalance,
+	pub average: TokenBalance,
+}
+
+/// Summary of a settled epoch, kept SCALE-encoded for bridges to relay
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct Resolution<BlockNumber, TokenBalance> {
+	pub value: i64,
+	pub epoch_start: BlockNumber,
+	pub revealed: u32,
+	pub winners: u32,
+	pub deposits: DepositStats<TokenBalance>,
+}
+
+/// Record of a governance override of the resolved value
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct ValueOverride<BlockNumber, Hash> {
+	pub topic: TopicId,
+	pub block_number: BlockNumber,
+	pub old_value: i64,
+	pub new_value: i64,
+	pub reason_hash: Hash,
+}
+
+/// How the indices of the 25th and 75th percentiles are computed for a sorted list of `n` values
+///
+/// `Floor` takes `floor(p * n)` as the index of the percentile `p`, `Ceil` uses the
+/// nearest-rank method, the value at rank `ceil(p * n)` or index `ceil(p * n) - 1`.
+/// `Exclusive` rewards only the values strictly between both percentiles, `Inclusive`
+/// rewards the percentiles too. `InclusiveCeil` is the common textbook (nearest-rank)
+/// interquartile range, `ExclusiveFloor` is the original behaviour.
+/// `Interpolated` is the default, it rewards every index between `0.25 * (n - 1)` and
+/// `0.75 * (n - 1)` inclusive, the positions of both percentiles under linear interpolation.
+/// The band is symmetric around the median, never empty from 3 values on and empty below.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum PercentileMode {
+	ExclusiveFloor,
+	ExclusiveCeil,
+	InclusiveFloor,
+	InclusiveCeil,
+	Interpolated,
+}
+
+impl Default for PercentileMode {
+	fn default() -> Self {
+		PercentileMode::Interpolated
+	}
+}
+
+/// Where the forfeited share of an unrevealed commitment goes, on settlement and on withdrawal alike
+///
+/// Only a forfeit reaching token_base is recycled into the reward pool.
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq)]
+pub enum ForfeitDestination<AccountId> {
+	/// Unlocked to token_base, like the penalties
+	Base,
+	/// Unlocked to a treasury account
+	Treasury(AccountId),
+	/// Removed from the total supply
+	Burn,
+}
+
+impl<AccountId> Default for ForfeitDestination<AccountId> {
+	fn default() -> Self {
+		ForfeitDestination::Base
+	}
+}
+
+/// Round of the epoch the chain is in
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq)]
+pub enum EpochPhase {
+	/// No epoch was started or the last one was resolved
+	Inactive,
+	/// Hashes are committed
+	Commit,
+	/// Committed values are revealed
+	Reveal,
+	/// The reveal round is over and the epoch can be settled
+	Resolution,
+}
+
+impl Default for EpochPhase {
+	fn default() -> Self {
+		EpochPhase::Inactive
+	}
+}
+
+/// Snapshot of the current epoch, returned at once to save clients several queries
+#[cfg_attr(feature = "std", derive(Debug))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct EpochState<BlockNumber, TokenBalance> {
+	pub epoch_start: BlockNumber,
+	pub phase: EpochPhase,
+	pub committed: u32,
+	pub revealed: u32,
+	pub total_locked: TokenBalance,
+	/// Blocks left until the end of the current phase, zero once the epoch can be settled
+	pub blocks_remaining: BlockNumber,
 }
 
 decl_storage! {
 	trait Store for Module<T: Trait> as SchellingStorage {
 
 		// Address that we send rewards from
-        pub TokenBase get(token_base): T::AccountId;
+        pub TokenBase get(token_base) config(): T::AccountId;
 
 		// BlockNumber of a new epoch being started 
-        pub EpochStart get(epoch_start): T::BlockNumber;
+        pub EpochStart get(epoch_start) build(|config: &GenesisConfig<T>| vec![(DEFAULT_TOPIC, config.epoch_start.clone())]): map TopicId => T::BlockNumber;
+
+        // Topics created besides the default one, every topic runs its own epochs
+        pub Topics get(topics): Vec<TopicId>;
+
+        // Number of epochs of a topic started so far, lets locks count down whether their account takes part or not
+        pub EpochIndex get(epoch_index): map TopicId => u64;
+
+        // Whether an epoch was started and is not resolved yet
+        pub EpochActive get(epoch_active) build(|config: &GenesisConfig<T>| vec![(DEFAULT_TOPIC, !config.epoch_start.is_zero())]): map TopicId => bool;
+
+        // Length in blocks of the hash submission round of a topic
+        pub HashSubmissionBlocks get(hash_submission_blocks): map TopicId => T::BlockNumber = T::BlockNumber::sa(50);
+
+        // Length in blocks of the value submission round of a topic, the epoch can be settled once it is over
+        pub ValueSubmissionBlocks get(value_submission_blocks): map TopicId => T::BlockNumber = T::BlockNumber::sa(50);
+
+        // Round durations set during an epoch of a topic, taken over when its next epoch starts
+        pub PendingRoundDurations get(pending_round_durations): map TopicId => Option<(T::BlockNumber, T::BlockNumber)>;
+
+        // Blocks the value submission round of the current epoch was extended by
+        pub RevealExtension get(reveal_extension): map TopicId => T::BlockNumber;
         
         // All the messages being submitted in the following epoch
-        pub Messages get(messages): map T::AccountId => Message<T::AccountId, T::Hash, T::TokenBalance>;
+        pub Messages: double_map TopicId, blake2_256(T::AccountId) => Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>;
+
+        // Accounts with a message in the Messages mapping, as maps can not be iterated
+        pub Committers get(committers): map TopicId => Vec<T::AccountId>;
 		
 		// Messages that passed our checks
-        pub ValidMessages get(valid_messages): Vec<Message<T::AccountId, T::Hash, T::TokenBalance>>;
+        pub ValidMessages get(valid_messages): map TopicId => Vec<Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>>;
 	
 		// Output of our alrorithm, source of wisdom of the crowd 
-        pub Value get(value): u64;
+        pub Value get(value): map TopicId => i64;
+
+        // Confidence in the last resolved value, from participation and spread
+        pub LastConfidence get(last_confidence): Perbill;
+
+        // Sum of the penalties collected over all the epochs
+        pub LifetimePenalties get(lifetime_penalties): T::TokenBalance;
+
+        // Penalized deposits are refunded in multiples of this amount, the remainder goes to token_base with the penalty
+        pub DepositDustThreshold get(deposit_dust_threshold): T::TokenBalance;
+
+        // Accounts penalized in the last settled epoch, at most MAX_LAST_SLASHED
+        pub LastSlashed get(last_slashed): Vec<T::AccountId>;
+
+        // SCALE-encoded `Resolution` of the last settled epoch
+        pub LastResolutionEncoded get(last_resolution_encoded): Vec<u8>;
+
+        // Block Value was last set in
+        pub ValueSetBlock get(value_set_block): T::BlockNumber;
+
+        // Whether Value was carried forward from a previous epoch by an invalidated epoch
+        pub ValueCarried get(value_carried): bool;
+
+        // Weight in basis points of the previous value in the resolved value, no smoothing if zero
+        pub SmoothingBps get(smoothing_bps): u32;
+
+        // Values resolved by the last epochs with the start of their epoch, oldest first
+        pub ValueHistory get(value_history): Vec<(T::BlockNumber, i64)>;
+
+        // Signed values resolved by the last epochs of a fixed point feed, pruned like ValueHistory
+        pub FixedValueHistory get(fixed_value_history): Vec<(T::BlockNumber, i128)>;
+
+        // Number of rewarded messages of the last resolved epochs with the start of their epoch, oldest first
+        pub WinnerHistory get(winner_history): Vec<(T::BlockNumber, u32)>;
+
+        // Number of resolved epochs kept in ValueHistory and WinnerHistory, no history if zero
+        pub MaxHistoryLength get(max_history_length): u32 = 64;
+
+        // Incremented on every write of Value, lets readers detect updates
+        pub ValueVersion get(value_version): u64;
+
+        // Hash of the encoded sorted values the last resolution was computed over
+        pub LastDatasetHash get(last_dataset_hash): T::Hash;
+
+        // Sorted values the last resolution was computed over, the lowest MAX_LAST_SORTED_VALUES of them
+        pub LastSortedValues get(last_sorted_values): Vec<i64>;
+
+        // Sorted signed values of the last resolution of a fixed point feed, the lowest MAX_LAST_SORTED_VALUES of them
+        pub LastSortedFixedValues get(last_sorted_fixed_values): Vec<i128>;
+
+        // Accounts notified through OnNewValue on every resolution
+        pub Consumers get(consumers): Vec<T::AccountId>;
+
+        // Audit trail of all the governance overrides of Value
+        pub ValueOverrides get(value_overrides): Vec<ValueOverride<T::BlockNumber, T::Hash>>;
+
+        // Decimals and unit of the values of the feed
+        pub Feed get(feed_config): FeedConfig;
+
+        // Number of values revealed per message, a single value feed if not above 1
+        pub FeedWidth get(feed_width): u32;
+
+        // Reveals are signed fixed point values scaled by 10^decimals of the feed config
+        pub FixedPoint get(fixed_point): bool;
+
+        // Value resolved by a fixed point feed
+        pub FixedValue get(fixed_value): i128;
+
+        // Median of every position of a composite feed, per topic
+        pub CompositeValue get(composite_value): map TopicId => Vec<i64>;
+
+        // Accept values submitted directly with their deposit, without a commitment
+        pub DirectSubmitMode get(direct_submit_mode): bool;
+
+        // Reject new commitments, reveals and withdrawals of the pending ones stay possible
+        pub CommitsPaused get(commits_paused): bool;
+
+        // Set by emergency_halt, no submission is accepted and no epoch runs until resume
+        pub Halted get(halted): bool;
+
+        // Use the size of the deposits as the signal instead of revealed values
+        pub StakeSignalMode get(stake_signal_mode): bool;
+
+        // Block of the last accepted reveal
+        pub LastRevealBlock get(last_reveal_block): map TopicId => T::BlockNumber;
+
+        // Blocks without a reveal after which the epoch is flagged as stalling, disabled if zero
+        pub RevealGapLimit get(reveal_gap_limit): T::BlockNumber;
+
+        // Whether no reveal arrived within RevealGapLimit during the reveal round
+        pub Stalling get(stalling): map TopicId => bool;
 
         // Minimal deposit
-        pub MinDeposit get(min_deposit): T::TokenBalance;
+        // only checked on commit, messages locked under a lower minimum still resolve normally
+        pub MinDeposit get(min_deposit) config(): T::TokenBalance;
+
+        // Maximal deposit of a commitment, top ups included, disabled if zero
+        pub MaxDeposit get(max_deposit): T::TokenBalance;
+
+        // Minimal sum of the revealed deposits for send_rewards to set a value
+        pub MinTotalStake get(min_total_stake): T::TokenBalance;
+
+        // Minimal share of the committers in basis points that revealed for the epoch to set a value
+        pub MinRevealRatioBps get(min_reveal_ratio_bps): u32;
+
+        // Account that funded an account, accounts of the same funder are treated as one
+        pub Funders get(funder): map T::AccountId => Option<T::AccountId>;
+
+        // Maximal share of the revealed stake in basis points held by the accounts of one funder, disabled if zero
+        pub MaxFunderShareBps get(max_funder_share_bps): u32;
+
+        // Minimal number of distinct funders behind the revealed values, accounts without a funder
+        // count as their own funder, disabled if zero
+        pub MinUniqueFunders get(min_unique_funders): u32;
+
+        // Account that triggered the last resolution through trigger_resolution
+        pub LastResolver get(last_resolver): Option<T::AccountId>;
+
+        // Full reward of a submission inside the reward band, paid from token_base
+        pub RewardPerSubmission get(reward_per_submission): T::TokenBalance = T::TokenBalance::sa(100);
+
+        // Reward split among the winners of every epoch instead of RewardPerSubmission to each, zero to disable
+        pub FixedPoolReward get(fixed_pool_reward): T::TokenBalance;
+
+        // Paid from token_base to the account triggering a resolution
+        pub ResolverReward get(resolver_reward): T::TokenBalance;
+
+        // First account to reveal in the current epoch
+        pub FirstRevealer get(first_revealer): map TopicId => Option<T::AccountId>;
+
+        // Paid from token_base to the first revealer of an epoch on resolution
+        pub FirstRevealerBonus get(first_revealer_bonus): T::TokenBalance;
+
+        // Share of the deposit in basis points kept from commitments not revealed in their reveal round,
+        // whether they are withdrawn after the deadline or settled with the epoch, the whole deposit by default
+        pub UnrevealedForfeitBps get(unrevealed_forfeit_bps): u32 = 10_000;
+
+        // Where the forfeited share of unrevealed commitments goes
+        pub UnrevealedForfeitDestination get(forfeit_destination): ForfeitDestination<T::AccountId>;
+
+        // Maximal size of an encoded revealed value in bytes
+        pub MaxRevealEncodedLen get(max_reveal_encoded_len): u32 = 32;
+
+        // Maximal number of values accepted per epoch, zero for no limit
+        pub MaxSubmissions get(max_submissions): u32;
+
+        // Share of the penalties in basis points added to the reward pool of the next epoch
+        pub PenaltyRecycleBps get(penalty_recycle_bps): u32;
+
+        // Reward budget shared among the winners of a topic on top of their reward, funded by the penalties of the topic
+        pub RewardPool get(reward_pool): map TopicId => T::TokenBalance;
+
+        // How the borders of the reward band are computed
+        pub RewardPercentileMode get(percentile_mode): PercentileMode;
+
+        // Concentric reward bands replacing the percentile band when not empty, innermost first
+        pub RewardTiers get(reward_tiers): Vec<RewardTier>;
+
+        // Amounts of the assets besides the main token token_base pays to every winner on top of its reward
+        pub RewardBundles get(reward_bundles): Vec<(token::AssetId, T::TokenBalance)>;
+
+        // Resolution parameters in effect when the first commitment of the current epoch was made
+        pub ConfigSnapshot get(config_snapshot): map TopicId => Option<EpochConfig<T::TokenBalance>>;
+
+        // Leave zero values out of the median and the reward band, zero meaning no data
+        pub IgnoreZeroValues get(ignore_zero_values): bool;
+
+        // Cap on the sum of the rewards paid out in one epoch, disabled if zero
+        pub MaxEpochPayout get(max_epoch_payout): T::TokenBalance;
+
+        // Share of the reward in basis points withheld from reveals after CopyGuardThreshold
+        pub CopyGuardBps get(copy_guard_bps): u32;
+
+        // Blocks into the reveal round after which reveals get a reduced reward
+        pub CopyGuardThreshold get(copy_guard_threshold): T::BlockNumber;
+
+        // Scale rewards inside the band by the distance from the median
+        pub WeightedRewards get(weighted_rewards): bool;
+
+        // Weight every value by its deposit when resolving the median of a plain value feed
+        pub WeightedMode get(weighted_mode): bool;
+
+        // Values at most this far from the median all get the top reward, zero for exact ties only
+        pub MedianTolerance get(median_tolerance): u64;
+
+        // Reward paid instead of the penalty to values outside of the band within ConsolationTolerance of the median
+        pub ConsolationReward get(consolation_reward): T::TokenBalance;
+        pub ConsolationTolerance get(consolation_tolerance): u64;
+
+        // Record rewards as claimable instead of paying them out on resolution
+        pub EscrowRewards get(escrow_rewards): bool;
+
+        // Blocks after the resolution during which escrowed rewards can be claimed
+        pub ClaimPeriod get(claim_period): T::BlockNumber;
+
+        // Escrowed reward of an account for the epoch of the topic starting at the given block
+        pub ClaimableRewards get(claimable_reward): map (TopicId, T::AccountId, T::BlockNumber) => T::TokenBalance;
+
+        // Last block to claim the escrowed rewards of the epoch of the topic starting at the given block
+        pub ClaimDeadlines get(claim_deadline): map (TopicId, T::BlockNumber) => T::BlockNumber;
+
+        // Sum of all the escrowed rewards not claimed or expired yet
+        pub EscrowedRewards get(escrowed_rewards): T::TokenBalance;
+
+        // Accounts that keep their settled deposits locked for the next commit
+        pub AutoRollover get(auto_rollover): map T::AccountId => bool;
+
+        // Deposits rolled over from previous epochs, used first by the next commit
+        pub StandingDeposits get(standing_deposit): map T::AccountId => T::TokenBalance;
+
+        // Index of the first epoch of the topic the deposit of an account is no longer locked in,
+        // the settled deposit is kept as a standing deposit until then
+        pub LockEnds get(lock_end): map (TopicId, T::AccountId) => u64;
+
+        // Reward bonus in basis points for every settled epoch a deposit stays locked beyond
+        pub LongTermBonusBps get(long_term_bonus_bps): u32;
+
+        // Accumulate rewards as earnings withdrawn separately instead of paying them out on resolution
+        pub AccrueEarnings get(accrue_earnings): bool;
+
+        // Running average distance of the values of an account from the resolved medians of a topic
+        pub Accuracies get(accuracy): map (TopicId, T::AccountId) => Accuracy;
+
+        // Rewards accrued by an account over all epochs and not withdrawn yet
+        pub AccruedEarnings get(accrued_earnings): map T::AccountId => T::TokenBalance;
+
+        // Number of epochs of all the topics an account was rewarded in
+        pub Reputation get(reputation): map T::AccountId => u32;
+
+        // Accounts with a reputation in ascending order, as maps can not be iterated
+        pub ReputationAccounts get(reputation_accounts): Vec<T::AccountId>;
+
+        // Accounts that revealed in the epoch starting at the given block
+        RevealedSet: double_map TopicId, blake2_256((T::AccountId, T::BlockNumber)) => bool;
+
+        // Evidence attached to the reveal of an account in the epoch starting at the given block, kept after settlement
+        pub RevealEvidence get(reveal_evidence): map (T::AccountId, T::BlockNumber) => T::Hash;
+
+        // Number of reveals of every value in the current epoch
+        RevealedValueCounts: double_map TopicId, blake2_256(i64) => u32;
+
+        // Number of distinct values revealed in the current epoch
+        pub DistinctValueCount get(distinct_value_count): map TopicId => u32;
+	}
+	add_extra_genesis {
+		config(epoch_start): T::BlockNumber;
+
+		build(|_: &mut runtime_primitives::StorageOverlay, _: &mut runtime_primitives::ChildrenStorageOverlay, config: &GenesisConfig<T>| {
+			assert!(!config.min_deposit.is_zero(), "The genesis min_deposit must not be zero");
+		});
 	}
 }
 
@@ -51,224 +598,2835 @@ decl_module! {
 
 		fn deposit_event<T>() = default;
 
-		fn new_epoch(origin) -> Result{
-			let _root = ensure_root(origin)?;
+		// settle the epoch of every topic once its reveal round is over and flag the epoch as stalling
+		// when the gap since the last reveal grows too large
+		fn on_finalize(n: T::BlockNumber) {
+			for topic in Self::all_topics() {
+				Self::finalize_topic(topic, n);
+			}
+		}
 
-			let block_number = <system::Module<T>>::block_number();
-			<EpochStart<T>>::put(block_number.clone());		
+		fn new_epoch(origin, topic: TopicId) -> Result{
+			let _root = ensure_root(origin)?;
+			ensure!(!Self::halted(), "The oracle is halted, use resume");
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(!Self::epoch_active(topic), "The current epoch is not resolved yet, use force_new_epoch");
 
-			// emit event that new epoch has started
-			Self::deposit_event(RawEvent::NewEpochStarted(block_number));
+			Self::start_epoch(topic);
 
 			Ok(())	
 		}
 
-		fn submit_hash(origin, hash: T::Hash, #[compact] deposit: T::TokenBalance) -> Result{
-			let sender = ensure_signed(origin)?;
-			ensure!(!<Messages<T>>::exists(&sender), "There is a submission made by the message sender");		
-			ensure!(deposit >= Self::min_deposit(), "The deposit is not high enough");		
-			
-			let epoch_start = Self::epoch_start();
-			let block_number = <system::Module<T>>::block_number();
+		// add a topic resolved in its own epochs, the first one starts right away
+		fn create_topic(origin, id: TopicId) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(!Self::halted(), "The oracle is halted, use resume");
+			ensure!(!Self::topic_exists(id), "The topic already exists");
+			ensure!(Self::topics().len() < MAX_TOPICS, "Too many topics");
 
-			// deadline for hash submission 50 blocks after the epoch start
-			let deadline = epoch_start.checked_add(&T::BlockNumber::sa(50)).ok_or("Epoch_start Overflow")?;
-
-			ensure!(block_number < deadline, "The deadline for hash submission is passed, try next epoch");
-			
-			// lock the deposit of the sender
-			<token::Module<T>>::lock(sender.clone(), deposit.clone(), hash.clone())?;
-			
-			// compose a message and add to the message list
-			let message = Message{
-				owner: sender.clone(),
-				status: 1, 
-				hash: hash, 
-				value: 0,
-				deposit: deposit.clone(),
-			};
-			<Messages<T>>::insert(sender.clone(), message);
+			<Topics<T>>::mutate(|topics| topics.push(id));
 
-			// emit event that the hash was submitted
-			Self::deposit_event(RawEvent::HashSubmitted(sender, deposit));
+			// emit event that the topic was created
+			Self::deposit_event(RawEvent::TopicCreated(id));
+
+			Self::start_epoch(id);
 
 			Ok(())
 		}
 
-		fn submit_value(origin, #[compact] value: u64) -> Result{
-			let sender = ensure_signed(origin)?;
-			ensure!(<Messages<T>>::exists(&sender), "Message hash was not submitted");
-			
-			let epoch_start = Self::epoch_start();
-			let block_number = <system::Module<T>>::block_number();
+		// start a new epoch even if the current one was not resolved
+		// revealed deposits are returned and the previous value carries forward,
+		// commitments of the current epoch can only be withdrawn afterwards
+		fn force_new_epoch(origin, topic: TopicId) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(!Self::halted(), "The oracle is halted, use resume");
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+
+			if Self::valid_messages(topic).is_empty() {
+				Self::start_epoch(topic);
+
+				return Ok(());
+			}
 
-			// the end of the value submission round
-			let round_one_end = epoch_start.checked_add(&T::BlockNumber::sa(50)).ok_or("Round_one_end overflow")?;
-			
-			// the period for value submission is between 50 and 100 blocks after the epoch start
-			let deadline = epoch_start.checked_add(&T::BlockNumber::sa(100)).ok_or("Deadline verflow")?;
+			let total_stake = Self::valid_messages(topic).iter()
+				.fold(T::TokenBalance::zero(), |total, message| total.saturating_add(message.deposit));
+			Self::invalidate_epoch(topic, total_stake)
+		}
 
-			ensure!(block_number > round_one_end, "Hash submission round did not end yet");
-			ensure!(block_number < deadline, "The deadline for value submission is passed, please withdraw deposit");
-			
-			let mut message = Self::messages(&sender);
-			ensure!(message.status == 1, "Message status should be 1");
+		// stop the oracle: every pending commitment and revealed deposit is refunded in full
+		// and the current epoch of every topic is dropped without a value
+		fn emergency_halt(origin) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(!Self::halted(), "The oracle is already halted");
 
-			// compare the hash of account id and value with the hash being submitted
-			let tuple = (sender.clone(), message.value);
-			let random_hash = tuple.using_encoded(<T as system::Trait>::Hashing::hash);
-			ensure!(random_hash == message.hash, "Hashes do not match");
+			for topic in Self::all_topics() {
+				for who in Self::committers(topic) {
+					let message = <Messages<T>>::get(&topic, &who);
+					<token::Module<T>>::unlock(who.clone(), message.deposit, message.hash)?;
+					Self::remove_commitment(topic, &who);
+					<LockEnds<T>>::remove((topic, who));
+				}
 
-			// update message info and add to the list of valid messages
-			message.value = value.clone();
-			message.status = 2;
+				let epoch_start = Self::epoch_start(topic);
+				for message in Self::valid_messages(topic) {
+					<token::Module<T>>::unlock(message.owner.clone(), message.deposit, message.hash)?;
+					<RevealedValueCounts<T>>::remove(&topic, &message.value);
+					<RevealedSet<T>>::remove(&topic, &(message.owner.clone(), epoch_start));
+					<LockEnds<T>>::remove((topic, message.owner));
+				}
+				<ValidMessages<T>>::remove(topic);
+				<DistinctValueCount<T>>::remove(topic);
+				<FirstRevealer<T>>::remove(topic);
+				<ConfigSnapshot<T>>::remove(topic);
+				<EpochActive<T>>::insert(topic, false);
+			}
+			<Halted<T>>::put(true);
 
-			let mut valid_messages = Self::valid_messages();
-			valid_messages.push(message);
+			// emit event that the oracle was halted
+			Self::deposit_event(RawEvent::EmergencyHalted(<system::Module<T>>::block_number()));
 
-			<ValidMessages<T>>::put(valid_messages);
+			Ok(())
+		}
 
-			// emit event that the value submission was accepted
-			Self::deposit_event(RawEvent::ValueSubmissionAccepted(sender.clone(), value));
+		// lift the emergency halt and start a fresh epoch
+		fn resume(origin) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(Self::halted(), "The oracle is not halted");
 
-			// delete message from the map
-			<Messages<T>>::remove(sender);
+			<Halted<T>>::put(false);
+			for topic in Self::all_topics() {
+				Self::start_epoch(topic);
+			}
 
 			Ok(())
 		}
 
-		//  function for deposit withdrawal the case when message was not validated
-		fn withdraw(origin) -> Result{
+		// the committed hash must be `Hashing::hash_of(&(sender, value, salt))`, with the values
+		// of a composite feed or the fixed point value of a fixed point feed in place of `value`
+		fn submit_hash(origin, topic: TopicId, hash: T::Hash, #[compact] deposit: T::TokenBalance) -> Result{
 			let sender = ensure_signed(origin)?;
-			ensure!(<Messages<T>>::exists(&sender), "Message hash was not submitted");
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
 
-			let message = Self::messages(&sender);
-			ensure!(message.status == 1, "Message status should be 1");
-			<token::Module<T>>::unlock(message.owner, message.deposit, message.hash)?;
+			Self::commit(topic, sender, hash, deposit, 1)
+		}
 
-			// delete message from the map
-			<Messages<T>>::remove(sender.clone());
+		// commit and lock the deposit for `lock_duration` epochs, the current one included
+		// the settled deposit is kept as a standing deposit until the lock is over and the reward
+		// of every epoch settled while the lock runs on is raised by LongTermBonusBps
+		fn submit_locked_hash(origin, topic: TopicId, hash: T::Hash, #[compact] deposit: T::TokenBalance, lock_duration: u32) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(lock_duration >= 1 && lock_duration <= MAX_LOCK_DURATION, "Invalid lock duration");
 
-			// emit event that the deposit was withdrawn
-			Self::deposit_event(RawEvent::DepositWithdrawn(sender, message.deposit));
+			Self::commit(topic, sender, hash, deposit, lock_duration)
+		}
+
+		// commit on behalf of several accounts, each commitment signed by its account for the topic
+		// all the signatures are checked before any deposit is locked
+		fn relay_commits(origin, topic: TopicId, commits: Vec<(T::AccountId, T::Hash, T::TokenBalance, T::Signature)>) -> Result {
+			let _relayer = ensure_signed(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(commits.len() <= MAX_RELAYED_COMMITS, "Too many commits in one batch");
+
+			let epoch_start = Self::epoch_start(topic);
+			for (who, hash, deposit, signature) in commits.iter() {
+				let payload = (topic, who, hash, deposit, epoch_start).encode();
+				ensure!(signature.verify(&payload[..], who), "Invalid commit signature");
+			}
+
+			for (who, hash, deposit, _) in commits {
+				Self::commit(topic, who, hash, deposit, 1)?;
+			}
 
 			Ok(())
 		}
 
-		fn send_rewards(origin) -> Result{
-			let _root = ensure_root(origin)?;
-			// TODO: add auto triggerring onFinalize
+		fn submit_value(origin, topic: TopicId, value: i64, salt: T::Hash) -> Result{
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::feed_width() <= 1, "The feed expects composite values, use submit_values");
+			ensure!(!Self::stake_signal_mode(), "The deposit is the signal, use confirm_stake");
+			ensure!(!Self::fixed_point(), "The feed expects fixed point values, use submit_fixed");
 
-			let epoch_start = Self::epoch_start();
-			// Should be triggered automatically on the 101st block after 
-			// the epoch start, in the current implementation it is called manually 
-			let epoch_end = epoch_start.checked_add(&T::BlockNumber::sa(101)).ok_or("epoch_end overflow")?;
-			let block_number = <system::Module<T>>::block_number();
+			Self::reveal(topic, sender, value, Vec::new(), 0, salt, T::Hash::default())
+		}
+
+		// reveal a value with the hash of the off-chain data backing it, kept with the message for disputes
+		fn submit_value_with_evidence(origin, topic: TopicId, value: i64, salt: T::Hash, evidence_hash: T::Hash) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::feed_width() <= 1, "The feed expects composite values, use submit_values");
+			ensure!(!Self::stake_signal_mode(), "The deposit is the signal, use confirm_stake");
+			ensure!(!Self::fixed_point(), "The feed expects fixed point values, use submit_fixed");
 
-			ensure!(block_number == epoch_end, "It's not the time to send out the rewards yet");
+			Self::reveal(topic, sender, value, Vec::new(), 0, salt, evidence_hash)
+		}
 
-			let mut valid_messages = Self::valid_messages();
+		// reveal a signed value of a fixed point feed, scaled by 10^decimals of the feed config
+		fn submit_fixed(origin, topic: TopicId, value: i128, salt: T::Hash) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::fixed_point(), "The feed is not a fixed point feed, use submit_value");
 
-			// implement quick sort over valid_messages by value submitted
-			valid_messages.sort_by_key(|k| k.value);
+			Self::reveal(topic, sender, 0, Vec::new(), value, salt, T::Hash::default())
+		}
 
-			let messages_length = valid_messages.len();
+		// submit a value with its deposit in one step, skipping the commit phase
+		// only for trusted low stakes feeds, the value is public as soon as it is submitted
+		fn submit_direct_value(origin, topic: TopicId, value: i64, #[compact] deposit: T::TokenBalance) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(!Self::halted(), "The oracle is halted");
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::direct_submit_mode(), "Direct submission is disabled, commit a hash first");
+			ensure!(sender != Self::token_base(), "The token_base account can not participate");
+			ensure!(Self::feed_width() <= 1 && !Self::stake_signal_mode() && !Self::fixed_point(), "The feed does not accept plain values");
+			ensure!(!<Messages<T>>::exists(&topic, &sender), "There is a submission made by the message sender");
+			ensure!(!Self::has_revealed(topic, sender.clone()), "A value was already submitted this epoch");
+			Self::ensure_deposit_bounds(deposit)?;
+			ensure!(!Self::submissions_full(topic), "The maximum number of submissions is reached");
 
-			// get 25th and 75th percentiles
-			let lower_border = messages_length.checked_div(4).ok_or("lower_boarder overflow")?;
-			let step = messages_length.checked_mul(3).ok_or("messages_length step overflow")?;
-			let upper_border = step.checked_div(4).ok_or("upper_border overflow")?;
+			let epoch_start = Self::epoch_start(topic);
+			let block_number = <system::Module<T>>::block_number();
+			let deadline = Self::reveal_deadline(topic, epoch_start).ok_or("Deadline overflow")?;
+			ensure!(block_number < deadline, "The deadline for value submission is passed");
 
-			// get median 
-			let median_index =  messages_length.checked_div(2).ok_or("median_index overflow")?;
-			<Value<T>>::put(valid_messages[median_index].value);
+			// the deposit is locked under a listing unique to the account and the epoch of the topic
+			let hash = (b"direct", topic, &sender, epoch_start).using_encoded(<T as system::Trait>::Hashing::hash);
+			if let Err(e) = <token::Module<T>>::lock(sender.clone(), deposit, hash) {
+				// emit event that the deposit could not be locked
+				Self::deposit_event(RawEvent::LockFailed(sender, deposit));
+				return Err(e);
+			}
 
-			// Emit event that new value is being set
-			Self::deposit_event(RawEvent::NewValueSet(valid_messages[median_index].value));
+			let message = Message {
+				owner: sender,
+				status: 2,
+				hash,
+				value,
+				values: Vec::new(),
+				fixed: 0,
+				deposit,
+				epoch: epoch_start,
+				revealed_at: block_number,
+				lock_duration: 1,
+				evidence: T::Hash::default(),
+				delegates: Vec::new(),
+				threshold: 0,
+				approvals: Vec::new(),
+			};
+			Self::snapshot_config(topic);
+			Self::accept_value(topic, message, block_number);
 
-			let mut i = 0;
+			Ok(())
+		}
 
-			for message in valid_messages.iter(){
-				// if inside 25 and and 75 percentile range 
-				if i > lower_border && i < upper_border{
-					// unlock deposits
-					let message_clone = message.clone();
-					let owner = message_clone.owner.clone();
+		// reveal in stake signal mode, the deposit of the message is the revealed value
+		// the commitment is made to the deposit normalized to a number of minimal deposits
+		fn confirm_stake(origin, topic: TopicId, salt: T::Hash) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::stake_signal_mode(), "Stake signal mode is disabled, use submit_value");
 
-					<token::Module<T>>::unlock(message_clone.owner, message_clone.deposit, message_clone.hash)?;
+			let value = Self::stake_signal(<Messages<T>>::get(&topic, &sender).deposit);
+			Self::reveal(topic, sender, value, Vec::new(), 0, salt, T::Hash::default())
+		}
 
-					// send rewards from token_base
-					let token_base = Self::token_base();
-					let origin_clone = system::RawOrigin::Root.into();
-					<token::Module<T>>::transfer_from(origin_clone, token_base, owner, T::TokenBalance::sa(100))?;					
-				// if out of the range
-				} else {
-					let message_clone = message.clone();
-					let deposit = message_clone.deposit;
-
-					// get the 99 percent of the deposit token refund 
-					let step = deposit.clone().checked_mul(&T::TokenBalance::sa(99)).ok_or("deposit step overflow")?;
-					let refund = step.checked_div(&T::TokenBalance::sa(100)).ok_or("refund overflow")?;
-					let penalty = deposit.checked_sub(&refund).ok_or("penalty overflow")?;
-
-					// send back deposits after subtration of penalties
-					<token::Module<T>>::unlock(message_clone.owner, refund, message_clone.hash)?;
-					
-					// send penalties to token_base
-					let token_base = Self::token_base();
-					<token::Module<T>>::unlock(token_base, penalty, message_clone.hash)?;					
-					
-				}
-				i = i.checked_add(1).ok_or("Valid Messages vec index overflow")?;
-			}
-			let origin_clone = system::RawOrigin::Root.into(); 
-			
-			//replace ValidMessages array with an empty one
-			<ValidMessages<T>>::put(Vec::new());
+		// reveal all the positions of a composite feed at once
+		// the first position is the one used to sort messages and pay out rewards
+		fn submit_values(origin, topic: TopicId, values: Vec<i64>, salt: T::Hash) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			let width = Self::feed_width();
+			ensure!(width > 1, "The feed is not composite, use submit_value");
+			ensure!(values.len() == width as usize, "The number of values does not match the feed width");
 
-			Self::new_epoch(origin_clone)			
+			Self::reveal(topic, sender, values[0], values, 0, salt, T::Hash::default())
 		}
-		
-	}
-}
 
-decl_event!(
-	pub enum Event<T> where AccountId = <T as system::Trait>::AccountId,
-							Balance = <T as token::Trait>::TokenBalance,
-							BlockNumber = <T as system::Trait>::BlockNumber,
-	{
+		// let `threshold` of up to MAX_REVEAL_DELEGATES accounts reveal the pending commitment together,
+		// e.g. 2 of the 3 keys of an institutional reporter, the owner can still reveal itself
+		// no delegates and a zero threshold remove the delegation
+		fn set_reveal_delegates(origin, topic: TopicId, delegates: Vec<T::AccountId>, threshold: u32) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Messages<T>>::exists(&topic, &sender), "Message hash was not submitted");
+			ensure!(delegates.len() <= MAX_REVEAL_DELEGATES, "Too many reveal delegates");
+			ensure!(
+				(delegates.is_empty() && threshold == 0) || (threshold >= 1 && threshold as usize <= delegates.len()),
+				"Invalid delegate threshold"
+			);
+			ensure!(
+				delegates.iter().enumerate().all(|(i, delegate)| !delegates[..i].contains(delegate)),
+				"The reveal delegates must be distinct"
+			);
 
-		NewEpochStarted(BlockNumber),
-		HashSubmitted(AccountId, Balance),
-		ValueSubmissionAccepted(AccountId, u64),
-		DepositWithdrawn(AccountId, Balance),
-		NewValueSet(u64),
+			let mut message = <Messages<T>>::get(&topic, &sender);
+			ensure!(message.status == 1 && message.epoch == Self::epoch_start(topic), "There is no pending commitment in this epoch");
+			message.delegates = delegates;
+			message.threshold = threshold;
+			message.approvals = Vec::new();
+			<Messages<T>>::insert(&topic, &sender, &message);
 
-	}
-);
+			// emit event that the reveal of the commitment was delegated
+			Self::deposit_event(RawEvent::RevealDelegatesSet(sender, threshold));
 
-/// tests for this module
-#[cfg(test)]
-mod tests {
-	use super::*;
+			Ok(())
+		}
 
-	use primitives::{Blake2Hasher, H256};
-	use runtime_io::with_externalities;
-	use runtime_primitives::{
-		testing::{Digest, DigestItem, Header, UintAuthorityId},
-		traits::{BlakeTwo256, IdentityLookup},
-		BuildStorage,
-  	};
-  	use support::{assert_noop, assert_ok, impl_outer_origin};
+		// approve the reveal of the commitment of `owner` as one of its delegates, only the committed value
+		// can be approved and it is revealed for the owner with the approval reaching the threshold
+		fn approve_reveal(origin, topic: TopicId, owner: T::AccountId, value: i64, salt: T::Hash) -> Result {
+			let delegate = ensure_signed(origin)?;
+			ensure!(!Self::halted(), "The oracle is halted");
+			ensure!(Self::feed_width() <= 1 && !Self::stake_signal_mode() && !Self::fixed_point(), "The feed does not accept plain values");
+			ensure!(<Messages<T>>::exists(&topic, &owner), "Message hash was not submitted");
 
-  	impl_outer_origin! {
-    	pub enum Origin for Test {}
-  	}
+			let mut message = <Messages<T>>::get(&topic, &owner);
+			ensure!(message.delegates.contains(&delegate), "The account is not a reveal delegate of the commitment");
+			ensure!(!message.approvals.contains(&delegate), "The delegate already approved the reveal");
+			ensure!(message.status == 1 && message.epoch == Self::epoch_start(topic), "There is no pending commitment in this epoch");
 
-	#[derive(Clone, Eq, PartialEq)]
-	pub struct Test;
-	impl system::Trait for Test {
-	  	type Origin = Origin;
+			let epoch_start = message.epoch;
+			let block_number = <system::Module<T>>::block_number();
+			let round_one_end = Self::round_one_end(topic, epoch_start).ok_or("Round_one_end overflow")?;
+			let deadline = Self::reveal_deadline(topic, epoch_start).ok_or("Deadline overflow")?;
+			ensure!(block_number > round_one_end && block_number < deadline, "Reveals can only be approved during the value submission round");
+			ensure!(
+				<T as system::Trait>::Hashing::hash_of(&(owner.clone(), value, salt)) == message.hash,
+				"The revealed value does not match the committed hash"
+			);
+
+			message.approvals.push(delegate.clone());
+			let approvals = message.approvals.len() as u32;
+			if approvals < message.threshold {
+				<Messages<T>>::insert(&topic, &owner, &message);
+			} else {
+				Self::reveal(topic, owner.clone(), value, Vec::new(), 0, salt, T::Hash::default())?;
+			}
+
+			// emit event that the delegate approved the reveal
+			Self::deposit_event(RawEvent::RevealApproved(delegate, owner, approvals));
+
+			Ok(())
+		}
+
+		// top up the deposit of the pending commitment, only during the hash submission round
+		// so deposits can not be raised once reveals are public
+		fn increase_deposit(origin, topic: TopicId, #[compact] amount: T::TokenBalance) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Messages<T>>::exists(&topic, &sender), "Message hash was not submitted");
+
+			let mut message = <Messages<T>>::get(&topic, &sender);
+			let epoch_start = Self::epoch_start(topic);
+			ensure!(message.status == 1 && message.epoch == epoch_start, "There is no pending commitment in this epoch");
+
+			let deadline = Self::round_one_end(topic, epoch_start).ok_or("Epoch_start Overflow")?;
+			ensure!(<system::Module<T>>::block_number() < deadline, "Deposits can only be increased during the hash submission round");
+
+			let deposit = message.deposit.checked_add(&amount).ok_or("deposit overflow")?;
+			let max_deposit = Self::max_deposit();
+			ensure!(max_deposit.is_zero() || deposit <= max_deposit, "The deposit exceeds the maximal deposit");
+
+			if let Err(e) = <token::Module<T>>::lock(sender.clone(), amount, message.hash) {
+				// emit event that the deposit could not be locked
+				Self::deposit_event(RawEvent::LockFailed(sender, amount));
+				return Err(e);
+			}
+			message.deposit = deposit;
+			<Messages<T>>::insert(&topic, &sender, &message);
+
+			// emit event that the deposit was increased
+			Self::deposit_event(RawEvent::DepositIncreased(sender, deposit));
+
+			Ok(())
+		}
+
+		// replace the hash and the deposit of the pending commitment, only during the hash submission round
+		// the difference to the old deposit is locked from or returned to the balance
+		fn update_hash(origin, topic: TopicId, new_hash: T::Hash, #[compact] new_deposit: T::TokenBalance) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Messages<T>>::exists(&topic, &sender), "Message hash was not submitted");
+
+			let mut message = <Messages<T>>::get(&topic, &sender);
+			ensure!(message.status == 1 && message.epoch == Self::epoch_start(topic), "There is no pending commitment in this epoch");
+			Self::ensure_can_commit(topic, &sender, new_deposit)?;
+
+			if new_deposit > message.deposit {
+				let top_up = new_deposit - message.deposit;
+				if let Err(e) = <token::Module<T>>::lock(sender.clone(), top_up, message.hash) {
+					// emit event that the deposit could not be locked
+					Self::deposit_event(RawEvent::LockFailed(sender, top_up));
+					return Err(e);
+				}
+			} else if new_deposit < message.deposit {
+				<token::Module<T>>::unlock(sender.clone(), message.deposit - new_deposit, message.hash)?;
+			}
+			<token::Module<T>>::relock(new_deposit, message.hash, new_hash)?;
+
+			message.hash = new_hash;
+			message.deposit = new_deposit;
+			// the approvals were given for the replaced hash
+			message.approvals.clear();
+			<Messages<T>>::insert(&topic, &sender, &message);
+
+			// emit event that the commitment was replaced
+			Self::deposit_event(RawEvent::HashUpdated(sender, new_hash, new_deposit));
+
+			Ok(())
+		}
+
+		//  function for deposit withdrawal the case when message was not validated
+		fn withdraw(origin, topic: TopicId) -> Result{
+			let sender = ensure_signed(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(<Messages<T>>::exists(&topic, &sender), "Message hash was not submitted");
+
+			let refund = Self::release_commitment(topic, &sender)?;
+
+			// emit event that the deposit was withdrawn
+			Self::deposit_event(RawEvent::DepositWithdrawn(sender, refund));
+
+			Ok(())
+		}
+
+		// withdraw the pending commitment and commit `new_hash` in the current epoch in one call
+		// the refund of the withdrawn commitment goes toward the new deposit, the rest is locked
+		// from the balance, everything is checked before the withdrawal
+		fn recommit(origin, topic: TopicId, new_hash: T::Hash, #[compact] deposit: T::TokenBalance) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(<Messages<T>>::exists(&topic, &sender), "Message hash was not submitted");
+
+			let message = <Messages<T>>::get(&topic, &sender);
+			let refund = Self::withdrawal_refund(topic, &message)?;
+			Self::ensure_can_commit(topic, &sender, deposit)?;
+
+			// a running lock keeps the whole refund in the standing deposit
+			let reused = if refund < deposit || Self::locked_epochs(topic, &sender) > 0 { refund } else { deposit };
+			let standing = Self::standing_deposit(&sender).checked_add(&reused).ok_or("standing deposit overflow")?;
+			if standing < deposit {
+				let from_balance = deposit - standing;
+				ensure!(<token::Module<T>>::balance_of(&sender) > from_balance, "Not enough balance.");
+			}
+
+			let forfeit = message.deposit.checked_sub(&refund).ok_or("forfeit overflow")?;
+			Self::pay_forfeit(topic, sender.clone(), forfeit, message.hash)?;
+			<token::Module<T>>::relock(reused, message.hash, Self::rollover_key(&sender))?;
+			if refund > reused {
+				<token::Module<T>>::unlock(sender.clone(), refund - reused, message.hash)?;
+			}
+			<StandingDeposits<T>>::insert(&sender, standing);
+			Self::remove_commitment(topic, &sender);
+
+			// emit event that the deposit was withdrawn
+			Self::deposit_event(RawEvent::DepositWithdrawn(sender.clone(), refund));
+
+			Self::commit(topic, sender, new_hash, deposit, 1)
+		}
+
+		// leave the oracle: refund the pending commitments of all the topics, the standing deposit and the
+		// accrued earnings and clear the account's settings, escrowed rewards stay claimable with claim_reward
+		fn exit(origin) -> Result {
+			let sender = ensure_signed(origin)?;
+			let topics = Self::all_topics();
+			ensure!(
+				!topics.iter().any(|topic| Self::valid_messages(topic).iter().any(|message| message.owner == sender)),
+				"The revealed deposit is settled on resolution, exit afterwards"
+			);
+			ensure!(topics.iter().all(|topic| Self::locked_epochs(*topic, &sender) == 0), "The deposit is locked for more epochs");
+
+			let earnings = Self::accrued_earnings(&sender);
+			let standing = Self::standing_deposit(&sender);
+			let mut returned = earnings.checked_add(&standing).ok_or("exit amount overflow")?;
+
+			for topic in topics {
+				if <Messages<T>>::exists(&topic, &sender) {
+					let refund = Self::release_commitment(topic, &sender)?;
+					returned = returned.checked_add(&refund).ok_or("exit amount overflow")?;
+				}
+			}
+			if !standing.is_zero() {
+				<token::Module<T>>::unlock(sender.clone(), standing, Self::rollover_key(&sender))?;
+			}
+			if !earnings.is_zero() {
+				let origin_clone = system::RawOrigin::Root.into();
+				<token::Module<T>>::transfer_from(origin_clone, Self::token_base(), sender.clone(), earnings)?;
+			}
+
+			<StandingDeposits<T>>::remove(&sender);
+			<AccruedEarnings<T>>::remove(&sender);
+			<AutoRollover<T>>::remove(&sender);
+			for topic in Self::all_topics() {
+				<LockEnds<T>>::remove((topic, sender.clone()));
+			}
+
+			// emit event that the account left the oracle
+			Self::deposit_event(RawEvent::AccountExited(sender, returned));
+
+			Ok(())
+		}
+
+		fn send_rewards(origin, topic: TopicId) -> Result{
+			let _root = ensure_root(origin)?;
+			ensure!(!Self::halted(), "The oracle is halted");
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::epoch_active(topic), "There is no active epoch to settle");
+
+			// the epoch is settled in on_finalize once the reveal round is over,
+			// this settles it right away if the hook did not run yet
+			Self::settle_epoch(topic)
+		}
+
+		// send the rewards once the reveal round closed, open to any account
+		// the resolver is recorded and paid ResolverReward from token_base
+		fn trigger_resolution(origin, topic: TopicId) -> Result {
+			let sender = ensure_signed(origin)?;
+			ensure!(!Self::halted(), "The oracle is halted");
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::epoch_active(topic), "There is no active epoch to settle");
+
+			Self::settle_epoch(topic)?;
+			<LastResolver<T>>::put(sender.clone());
+
+			// the epoch is settled by now, a reward token_base can not pay is skipped rather than failing the call
+			let reward = Self::resolver_reward();
+			let paid = !reward.is_zero() && Self::pay_from_base(&Self::token_base(), &sender, reward)?;
+			let reward = if paid { reward } else { T::TokenBalance::zero() };
+
+			// emit event that the account resolved the epoch
+			Self::deposit_event(RawEvent::ResolutionTriggered(sender, Self::value(topic), reward));
+
+			Ok(())
+		}
+
+		// settle the epoch without waiting for the end of the reveal round
+		fn force_send_rewards(origin, topic: TopicId) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(!Self::halted(), "The oracle is halted");
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::epoch_active(topic), "There is no active epoch to settle");
+
+			Self::distribute_rewards(topic)
+		}
+
+		// correct a wrongly resolved value of a topic, the reason is referenced by its hash
+		fn override_value(origin, topic: TopicId, new_value: i64, reason_hash: T::Hash) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+
+			let old_value = Self::value(topic);
+			let record = ValueOverride {
+				topic,
+				block_number: <system::Module<T>>::block_number(),
+				old_value,
+				new_value,
+				reason_hash,
+			};
+			<ValueOverrides<T>>::mutate(|overrides| overrides.push(record));
+			<Value<T>>::insert(topic, new_value);
+			// the block and the version of the value follow the default topic
+			if topic == DEFAULT_TOPIC {
+				<ValueSetBlock<T>>::put(<system::Module<T>>::block_number());
+				<ValueVersion<T>>::mutate(|version| *version = version.wrapping_add(1));
+			}
+
+			// emit event that the value was overridden
+			Self::deposit_event(RawEvent::ValueOverridden(topic, old_value, new_value, reason_hash));
+
+			Ok(())
+		}
+
+		// claim the escrowed reward of the epoch of `topic` starting at `epoch`
+		// a reward not claimed before the deadline expires and stays with token_base
+		fn claim_reward(origin, topic: TopicId, epoch: T::BlockNumber) -> Result {
+			let sender = ensure_signed(origin)?;
+			let key = (topic, sender.clone(), epoch);
+			ensure!(<ClaimableRewards<T>>::exists(&key), "There is no reward to claim for this epoch");
+
+			let amount = Self::claimable_reward(&key);
+			let escrowed = Self::escrowed_rewards().checked_sub(&amount).ok_or("escrowed rewards overflow")?;
+			let expired = <system::Module<T>>::block_number() > Self::claim_deadline((topic, epoch));
+
+			if !expired {
+				let origin_clone = system::RawOrigin::Root.into();
+				<token::Module<T>>::transfer_from(origin_clone, Self::token_base(), sender.clone(), amount)?;
+			}
+			<ClaimableRewards<T>>::remove(&key);
+			<EscrowedRewards<T>>::put(escrowed);
+
+			if expired {
+				// emit event that the reward went back to the fund
+				Self::deposit_event(RawEvent::RewardExpired(sender, amount));
+			} else {
+				// emit event that the reward was paid out
+				Self::deposit_event(RawEvent::RewardClaimed(sender, amount));
+			}
+
+			Ok(())
+		}
+
+		// withdraw all the earnings accrued over the previous epochs from token_base
+		fn withdraw_earnings(origin) -> Result {
+			let sender = ensure_signed(origin)?;
+			let earnings = Self::accrued_earnings(&sender);
+			ensure!(!earnings.is_zero(), "There are no earnings to withdraw");
+
+			let origin_clone = system::RawOrigin::Root.into();
+			<token::Module<T>>::transfer_from(origin_clone, Self::token_base(), sender.clone(), earnings)?;
+			<AccruedEarnings<T>>::remove(&sender);
+
+			// emit event that the earnings were withdrawn
+			Self::deposit_event(RawEvent::EarningsWithdrawn(sender, earnings));
+
+			Ok(())
+		}
+
+		fn set_accrue_earnings(origin, enabled: bool) -> Result {
+			let _root = ensure_root(origin)?;
+			<AccrueEarnings<T>>::put(enabled);
+
+			Ok(())
+		}
+
+		// set the economic parameters at once, nothing is applied unless the whole config is consistent
+		fn configure(origin, config: SchellingConfig<T::TokenBalance, T::BlockNumber>) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(!config.min_deposit.is_zero(), "The minimal deposit must not be zero");
+			ensure!(config.penalty_recycle_bps <= 10_000, "The recycled share can not exceed 10000 basis points");
+			Self::validate_reward_tiers(&config.reward_tiers)?;
+			Self::validate_round_durations(config.hash_submission_blocks, config.value_submission_blocks)?;
+
+			<MinDeposit<T>>::put(config.min_deposit);
+			<MinTotalStake<T>>::put(config.min_total_stake);
+			for topic in Self::all_topics() {
+				Self::schedule_round_durations(topic, config.hash_submission_blocks, config.value_submission_blocks);
+			}
+			<RewardPerSubmission<T>>::put(config.reward_per_submission);
+			<FixedPoolReward<T>>::put(config.fixed_pool_reward);
+			<RewardPercentileMode<T>>::put(config.percentile_mode);
+			<RewardTiers<T>>::put(config.reward_tiers);
+			<WeightedRewards<T>>::put(config.weighted_rewards);
+			<PenaltyRecycleBps<T>>::put(config.penalty_recycle_bps);
+			<StakeSignalMode<T>>::put(config.stake_signal_mode);
+
+			Self::deposit_event(RawEvent::MinDepositChanged(config.min_deposit));
+
+			Ok(())
+		}
+
+		fn add_consumer(origin, consumer: T::AccountId) -> Result {
+			let _root = ensure_root(origin)?;
+			let mut consumers = Self::consumers();
+			ensure!(!consumers.contains(&consumer), "The consumer is already registered");
+			ensure!(consumers.len() < MAX_CONSUMERS, "Too many consumers");
+			consumers.push(consumer);
+			<Consumers<T>>::put(consumers);
+
+			Ok(())
+		}
+
+		fn remove_consumer(origin, consumer: T::AccountId) -> Result {
+			let _root = ensure_root(origin)?;
+			let mut consumers = Self::consumers();
+			ensure!(consumers.contains(&consumer), "The consumer is not registered");
+			consumers.retain(|registered| *registered != consumer);
+			<Consumers<T>>::put(consumers);
+
+			Ok(())
+		}
+
+		fn set_max_history_length(origin, max_length: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			<MaxHistoryLength<T>>::put(max_length);
+
+			Ok(())
+		}
+
+		fn set_reward_escrow(origin, enabled: bool, claim_period: T::BlockNumber) -> Result {
+			let _root = ensure_root(origin)?;
+			<EscrowRewards<T>>::put(enabled);
+			<ClaimPeriod<T>>::put(claim_period);
+
+			Ok(())
+		}
+
+		// keep settled deposits locked as a standing deposit for the next commit
+		fn set_auto_rollover(origin, enabled: bool) -> Result {
+			let sender = ensure_signed(origin)?;
+			<AutoRollover<T>>::insert(sender, enabled);
+
+			Ok(())
+		}
+
+		fn set_feed_config(origin, decimals: u8, unit: Vec<u8>) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(unit.len() <= MAX_UNIT_LEN, "The unit label is too long");
+			<Feed<T>>::put(FeedConfig { decimals, unit });
+
+			Ok(())
+		}
+
+		fn set_fixed_point(origin, enabled: bool) -> Result {
+			let _root = ensure_root(origin)?;
+			<FixedPoint<T>>::put(enabled);
+
+			Ok(())
+		}
+
+		fn set_direct_submit_mode(origin, enabled: bool) -> Result {
+			let _root = ensure_root(origin)?;
+			<DirectSubmitMode<T>>::put(enabled);
+
+			Ok(())
+		}
+
+		// stop or resume new commitments in an emergency, the pending ones can still be revealed
+		fn set_commits_paused(origin, paused: bool) -> Result {
+			let _root = ensure_root(origin)?;
+			<CommitsPaused<T>>::put(paused);
+
+			Ok(())
+		}
+
+		fn set_stake_signal_mode(origin, enabled: bool) -> Result {
+			let _root = ensure_root(origin)?;
+			<StakeSignalMode<T>>::put(enabled);
+
+			Ok(())
+		}
+
+		// set the lengths of the hash and value submission rounds of a topic
+		// during an epoch of the topic the new durations only apply from its next epoch on
+		fn set_round_durations(origin, topic: TopicId, hash_blocks: T::BlockNumber, value_blocks: T::BlockNumber) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			Self::validate_round_durations(hash_blocks, value_blocks)?;
+			Self::schedule_round_durations(topic, hash_blocks, value_blocks);
+
+			Ok(())
+		}
+
+		// push back the reveal deadline and the resolution of the current epoch only,
+		// e.g. after a stall kept committers from revealing
+		fn extend_reveal_window(origin, topic: TopicId, extra_blocks: T::BlockNumber) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(Self::topic_exists(topic), "The topic does not exist");
+			ensure!(Self::epoch_active(topic), "There is no active epoch to extend");
+			ensure!(!extra_blocks.is_zero(), "The extension must not be zero");
+
+			let epoch_start = Self::epoch_start(topic);
+			let extension = Self::reveal_extension(topic).checked_add(&extra_blocks).ok_or("reveal extension overflow")?;
+			let deadline = Self::reveal_deadline(topic, epoch_start)
+				.and_then(|deadline| deadline.checked_add(&extra_blocks))
+				.ok_or("reveal_deadline overflow")?;
+			<RevealExtension<T>>::insert(topic, extension);
+
+			// emit event that the reveal round of the epoch was extended
+			Self::deposit_event(RawEvent::RevealWindowExtended(epoch_start, deadline));
+
+			Ok(())
+		}
+
+		fn set_reveal_gap_limit(origin, limit: T::BlockNumber) -> Result {
+			let _root = ensure_root(origin)?;
+			<RevealGapLimit<T>>::put(limit);
+
+			Ok(())
+		}
+
+		fn set_feed_width(origin, width: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(width <= MAX_FEED_WIDTH, "The feed width is too large");
+			ensure!(width <= 1 || Self::composite_encoded_len(width) <= Self::max_reveal_encoded_len(), "The reveal size limit is too small for the feed width");
+			<FeedWidth<T>>::put(width);
+
+			Ok(())
+		}
+
+		// only checked by later commits, deposits locked under the previous minimum are settled as usual
+		fn set_min_deposit(origin, #[compact] amount: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(!amount.is_zero(), "The minimal deposit must not be zero");
+			<MinDeposit<T>>::put(amount);
+
+			// emit event that the minimal deposit changed
+			Self::deposit_event(RawEvent::MinDepositChanged(amount));
+
+			Ok(())
+		}
+
+		// account the rewards are paid from and the penalties are sent to
+		fn set_token_base(origin, account: T::AccountId) -> Result {
+			let _root = ensure_root(origin)?;
+			<TokenBase<T>>::put(account.clone());
+
+			// emit event that the reward source changed
+			Self::deposit_event(RawEvent::TokenBaseChanged(account));
+
+			Ok(())
+		}
+
+		fn set_max_deposit(origin, #[compact] amount: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			<MaxDeposit<T>>::put(amount);
+
+			Ok(())
+		}
+
+		fn set_min_total_stake(origin, #[compact] stake: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			<MinTotalStake<T>>::put(stake);
+
+			Ok(())
+		}
+
+		fn set_min_reveal_ratio_bps(origin, bps: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(bps <= 10_000, "The reveal ratio can not exceed 10000 basis points");
+			<MinRevealRatioBps<T>>::put(bps);
+
+			Ok(())
+		}
+
+		// record the funder of an account, `None` clears it
+		fn set_funder(origin, who: T::AccountId, funder: Option<T::AccountId>) -> Result {
+			let _root = ensure_root(origin)?;
+			match funder {
+				Some(funder) => <Funders<T>>::insert(who, funder),
+				None => <Funders<T>>::remove(who),
+			}
+
+			Ok(())
+		}
+
+		fn set_max_funder_share_bps(origin, bps: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(bps <= 10_000, "The funder share can not exceed 10000 basis points");
+			<MaxFunderShareBps<T>>::put(bps);
+
+			Ok(())
+		}
+
+		fn set_min_unique_funders(origin, min_funders: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			<MinUniqueFunders<T>>::put(min_funders);
+
+			Ok(())
+		}
+
+		fn set_reward_per_submission(origin, #[compact] reward: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			<RewardPerSubmission<T>>::put(reward);
+
+			Ok(())
+		}
+
+		// keep the total reward of an epoch constant whatever the number of winners
+		fn set_fixed_pool_reward(origin, #[compact] reward: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			<FixedPoolReward<T>>::put(reward);
+
+			Ok(())
+		}
+
+		fn set_resolver_reward(origin, #[compact] reward: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			<ResolverReward<T>>::put(reward);
+
+			Ok(())
+		}
+
+		fn set_first_revealer_bonus(origin, #[compact] bonus: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			<FirstRevealerBonus<T>>::put(bonus);
+
+			Ok(())
+		}
+
+		fn set_unrevealed_forfeit(origin, bps: u32, destination: ForfeitDestination<T::AccountId>) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(bps <= 10_000, "The forfeit can not exceed 10000 basis points");
+			<UnrevealedForfeitBps<T>>::put(bps);
+			<UnrevealedForfeitDestination<T>>::put(destination);
+
+			Ok(())
+		}
+
+		fn set_max_reveal_encoded_len(origin, max_len: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			let width = Self::feed_width();
+			ensure!(width <= 1 || Self::composite_encoded_len(width) <= max_len, "The reveal size limit is too small for the feed width");
+			<MaxRevealEncodedLen<T>>::put(max_len);
+
+			Ok(())
+		}
+
+		// once the cap is reached further reveals are refused and their deposits can be withdrawn
+		fn set_max_submissions(origin, max_submissions: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			<MaxSubmissions<T>>::put(max_submissions);
+
+			Ok(())
+		}
+
+		fn set_smoothing_bps(origin, bps: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(bps <= 10_000, "The smoothing can not exceed 10000 basis points");
+			<SmoothingBps<T>>::put(bps);
+
+			Ok(())
+		}
+
+		fn set_penalty_recycle_bps(origin, bps: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(bps <= 10_000, "The recycled share can not exceed 10000 basis points");
+			<PenaltyRecycleBps<T>>::put(bps);
+
+			Ok(())
+		}
+
+		fn set_percentile_mode(origin, mode: PercentileMode) -> Result {
+			let _root = ensure_root(origin)?;
+			<RewardPercentileMode<T>>::put(mode);
+
+			Ok(())
+		}
+
+		// tiers are given innermost first with strictly increasing edges
+		fn set_reward_tiers(origin, tiers: Vec<RewardTier>) -> Result {
+			let _root = ensure_root(origin)?;
+			Self::validate_reward_tiers(&tiers)?;
+			<RewardTiers<T>>::put(tiers);
+
+			Ok(())
+		}
+
+		fn set_reward_bundles(origin, bundles: Vec<(token::AssetId, T::TokenBalance)>) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(bundles.len() <= MAX_REWARD_BUNDLES, "Too many reward assets");
+			ensure!(bundles.iter().all(|(_, amount)| !amount.is_zero()), "Reward asset amounts must not be zero");
+			ensure!(bundles.iter().enumerate().all(|(i, (asset, _))| bundles[..i].iter().all(|(other, _)| other != asset)), "Reward assets must be distinct");
+			<RewardBundles<T>>::put(bundles);
+
+			Ok(())
+		}
+
+		fn set_ignore_zero_values(origin, enabled: bool) -> Result {
+			let _root = ensure_root(origin)?;
+			<IgnoreZeroValues<T>>::put(enabled);
+
+			Ok(())
+		}
+
+		fn set_max_epoch_payout(origin, #[compact] max_payout: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			<MaxEpochPayout<T>>::put(max_payout);
+
+			Ok(())
+		}
+
+		fn set_copy_guard(origin, bps: u32, threshold: T::BlockNumber) -> Result {
+			let _root = ensure_root(origin)?;
+			ensure!(bps <= 10_000, "The copy guard can not exceed 10000 basis points");
+			<CopyGuardBps<T>>::put(bps);
+			<CopyGuardThreshold<T>>::put(threshold);
+
+			Ok(())
+		}
+
+		// bonus in basis points of the reward for every further epoch a deposit is locked for
+		fn set_long_term_bonus_bps(origin, bps: u32) -> Result {
+			let _root = ensure_root(origin)?;
+			<LongTermBonusBps<T>>::put(bps);
+
+			Ok(())
+		}
+
+		// enable or disable scaling of rewards by the distance from the median
+		fn set_weighted_rewards(origin, enabled: bool) -> Result {
+			let _root = ensure_root(origin)?;
+			<WeightedRewards<T>>::put(enabled);
+
+			Ok(())
+		}
+
+		// refund penalized deposits in multiples of `threshold`, zero refunds them to the unit
+		fn set_deposit_dust_threshold(origin, #[compact] threshold: T::TokenBalance) -> Result {
+			let _root = ensure_root(origin)?;
+			<DepositDustThreshold<T>>::put(threshold);
+
+			Ok(())
+		}
+
+		// resolve the deposit weighted median instead of the median of the values
+		fn set_weighted_mode(origin, enabled: bool) -> Result {
+			let _root = ensure_root(origin)?;
+			<WeightedMode<T>>::put(enabled);
+
+			Ok(())
+		}
+
+		fn set_median_tolerance(origin, tolerance: u64) -> Result {
+			let _root = ensure_root(origin)?;
+			<MedianTolerance<T>>::put(tolerance);
+
+			Ok(())
+		}
+
+		// set the consolation for values just outside of the band, a zero reward disables it
+		fn set_consolation_reward(origin, #[compact] reward: T::TokenBalance, tolerance: u64) -> Result {
+			let _root = ensure_root(origin)?;
+			<ConsolationReward<T>>::put(reward);
+			<ConsolationTolerance<T>>::put(tolerance);
+
+			Ok(())
+		}
+		
+	}
+}
+
+impl<T: Trait> Module<T> {
+	// statistics over the deposits of all committed and revealed messages
+	// computed on the fly, storage is not modified
+	pub fn deposit_stats() -> DepositStats<T::TokenBalance> {
+		let deposits: Vec<T::TokenBalance> = Self::committers(DEFAULT_TOPIC).iter()
+			.map(|who| <Messages<T>>::get(&DEFAULT_TOPIC, who).deposit)
+			.chain(Self::valid_messages(DEFAULT_TOPIC).iter().map(|message| message.deposit))
+			.collect();
+
+		let count = deposits.len();
+		if count == 0 {
+			return DepositStats::default();
+		}
+
+		let total = deposits.iter().fold(T::TokenBalance::default(), |total, deposit| total.saturating_add(*deposit));
+		let min = deposits.iter().min().cloned().unwrap_or_default();
+		let max = deposits.iter().max().cloned().unwrap_or_default();
+
+		DepositStats {
+			count: count as u32,
+			total,
+			min,
+			max,
+			average: total / <T::TokenBalance as As<usize>>::sa(count),
+		}
+	}
+
+	// return a settled deposit of `topic` to its owner, or keep it locked as a standing deposit
+	// if the owner opted into auto rollover or locked the deposit for more epochs of the topic
+	fn return_deposit(topic: TopicId, owner: T::AccountId, amount: T::TokenBalance, hash: T::Hash) -> Result {
+		// kept as a standing deposit when rolled over or locked past the epoch being settled
+		if !Self::auto_rollover(&owner) && Self::locked_epochs(topic, &owner) <= 1 {
+			return <token::Module<T>>::unlock(owner, amount, hash);
+		}
+
+		Self::add_standing_deposit(owner, amount, hash)
+	}
+
+	// move `amount` locked under `hash` to the standing deposit of `owner`
+	fn add_standing_deposit(owner: T::AccountId, amount: T::TokenBalance, hash: T::Hash) -> Result {
+		let standing = Self::standing_deposit(&owner).checked_add(&amount).ok_or("standing deposit overflow")?;
+		<token::Module<T>>::relock(amount, hash, Self::rollover_key(&owner))?;
+		<StandingDeposits<T>>::insert(owner, standing);
+
+		Ok(())
+	}
+
+	// record a reward of the epoch of `topic` as claimable instead of paying it out
+	fn escrow_reward(topic: TopicId, owner: T::AccountId, epoch: T::BlockNumber, reward: T::TokenBalance) -> Result {
+		let key = (topic, owner, epoch);
+		let claimable = Self::claimable_reward(&key).checked_add(&reward).ok_or("claimable reward overflow")?;
+		let escrowed = Self::escrowed_rewards().checked_add(&reward).ok_or("escrowed rewards overflow")?;
+
+		<ClaimableRewards<T>>::insert(&key, claimable);
+		<EscrowedRewards<T>>::put(escrowed);
+
+		Ok(())
+	}
+
+	// resolution parameters of the current epoch of `topic`, the current storage values until the first commitment
+	fn epoch_config(topic: TopicId) -> EpochConfig<T::TokenBalance> {
+		Self::config_snapshot(topic).unwrap_or_else(|| EpochConfig {
+			percentile_mode: Self::percentile_mode(),
+			reward_tiers: Self::reward_tiers(),
+			weighted_rewards: Self::weighted_rewards(),
+			penalty_recycle_bps: Self::penalty_recycle_bps(),
+			median_tolerance: Self::median_tolerance(),
+			consolation_tolerance: Self::consolation_tolerance(),
+			weighted_mode: Self::weighted_mode(),
+			reward_per_submission: Self::reward_per_submission(),
+			fixed_pool_reward: Self::fixed_pool_reward(),
+			reward_bundles: Self::reward_bundles(),
+		})
+	}
+
+	// fix the resolution parameters of the current epoch on its first commitment
+	fn snapshot_config(topic: TopicId) {
+		if Self::config_snapshot(topic).is_none() {
+			<ConfigSnapshot<T>>::insert(topic, Self::epoch_config(topic));
+		}
+	}
+
+	// epochs of `topic` the deposit of `who` stays locked for, the current one included
+	pub fn locked_epochs(topic: TopicId, who: &T::AccountId) -> u32 {
+		Self::lock_end((topic, who.clone())).saturating_sub(Self::epoch_index(topic)) as u32
+	}
+
+	// listing under which the standing deposit of an account is locked
+	fn rollover_key(who: &T::AccountId) -> T::Hash {
+		(b"rollover", who).using_encoded(<T as system::Trait>::Hashing::hash)
+	}
+
+	fn validate_round_durations(hash_blocks: T::BlockNumber, value_blocks: T::BlockNumber) -> Result {
+		ensure!(!hash_blocks.is_zero() && !value_blocks.is_zero(), "Round durations must not be zero");
+		ensure!(hash_blocks.checked_add(&value_blocks).is_some(), "Round durations overflow");
+
+		Ok(())
+	}
+
+	// during an epoch of `topic` the new durations only apply from its next epoch on
+	fn schedule_round_durations(topic: TopicId, hash_blocks: T::BlockNumber, value_blocks: T::BlockNumber) {
+		if Self::epoch_active(topic) {
+			<PendingRoundDurations<T>>::insert(topic, (hash_blocks, value_blocks));
+		} else {
+			<PendingRoundDurations<T>>::remove(topic);
+			Self::apply_round_durations(topic, hash_blocks, value_blocks);
+		}
+	}
+
+	fn apply_round_durations(topic: TopicId, hash_blocks: T::BlockNumber, value_blocks: T::BlockNumber) {
+		<HashSubmissionBlocks<T>>::insert(topic, hash_blocks);
+		<ValueSubmissionBlocks<T>>::insert(topic, value_blocks);
+
+		// emit event that the round durations of the topic changed
+		Self::deposit_event(RawEvent::RoundDurationsChanged(topic, hash_blocks, value_blocks));
+	}
+
+	fn remove_committer(topic: TopicId, who: &T::AccountId) {
+		<Committers<T>>::mutate(topic, |committers| committers.retain(|c| c != who));
+	}
+
+	// the default topic first, then the created topics
+	fn all_topics() -> Vec<TopicId> {
+		let mut topics = Self::topics();
+		topics.insert(0, DEFAULT_TOPIC);
+
+		topics
+	}
+
+	fn topic_exists(topic: TopicId) -> bool {
+		topic == DEFAULT_TOPIC || Self::topics().contains(&topic)
+	}
+
+	// settle the epoch of `topic` once its reveal round is over, or flag it as stalling
+	fn finalize_topic(topic: TopicId, n: T::BlockNumber) {
+		let reveal_deadline = Self::reveal_deadline(topic, Self::epoch_start(topic));
+		if Self::epoch_active(topic) && reveal_deadline.map_or(false, |deadline| n >= deadline) {
+			if let Err(e) = Self::settle_epoch(topic) {
+				// hooks can not fail, report the error and retry on the next block
+				runtime_io::print(e);
+				Self::deposit_event(RawEvent::AutoResolutionFailed(n));
+			}
+			return;
+		}
+
+		let limit = Self::reveal_gap_limit();
+		if limit.is_zero() || Self::stalling(topic) {
+			return;
+		}
+
+		let epoch_start = Self::epoch_start(topic);
+		let (round_one_end, deadline) = match (
+			Self::round_one_end(topic, epoch_start),
+			Self::reveal_deadline(topic, epoch_start),
+		) {
+			(Some(round_one_end), Some(deadline)) => (round_one_end, deadline),
+			_ => return,
+		};
+		if n <= round_one_end || n >= deadline {
+			return;
+		}
+
+		let last_reveal = Self::last_reveal_block(topic).max(round_one_end);
+		if n.checked_sub(&last_reveal).map_or(false, |gap| gap > limit) {
+			<Stalling<T>>::insert(topic, true);
+
+			// emit event that no reveal arrived for too long
+			Self::deposit_event(RawEvent::EpochStalling(epoch_start, last_reveal));
+		}
+	}
+
+	// round durations scheduled during the previous epoch of the topic are taken over
+	fn start_epoch(topic: TopicId) {
+		let block_number = <system::Module<T>>::block_number();
+		<EpochStart<T>>::insert(topic, block_number.clone());
+		<EpochActive<T>>::insert(topic, true);
+		<RevealExtension<T>>::remove(topic);
+		<Stalling<T>>::insert(topic, false);
+		<FirstRevealer<T>>::remove(topic);
+		<ConfigSnapshot<T>>::remove(topic);
+
+		if let Some((hash_blocks, value_blocks)) = <PendingRoundDurations<T>>::take(topic) {
+			Self::apply_round_durations(topic, hash_blocks, value_blocks);
+		}
+		<EpochIndex<T>>::mutate(topic, |index| *index = index.saturating_add(1));
+
+		if topic != DEFAULT_TOPIC {
+			// emit event that new epoch of the topic has started
+			Self::deposit_event(RawEvent::TopicEpochStarted(topic, block_number));
+			return;
+		}
+
+		// emit event that new epoch has started
+		Self::deposit_event(RawEvent::NewEpochStarted(block_number));
+	}
+
+	// settle the current epoch: set the new value, pay out rewards and penalties
+	// and start the next epoch
+	fn distribute_rewards(topic: TopicId) -> Result {
+		// fail before any deposit is settled rather than in the middle of the payouts
+		ensure!(Self::token_base() != T::AccountId::default(), "The reward source token_base is not configured");
+
+		let epoch_start = Self::epoch_start(topic);
+		let block_number = <system::Module<T>>::block_number();
+
+		// an epoch without reveals keeps the current value and rolls over
+		if Self::valid_messages(topic).is_empty() {
+			// emit event that the epoch ended without a value
+			Self::deposit_event(RawEvent::EpochEndedWithNoValue(block_number));
+
+			if topic == DEFAULT_TOPIC {
+				<LastSlashed<T>>::kill();
+			}
+			Self::start_epoch(topic);
+
+			return Ok(());
+		}
+
+		let (valid_messages, abstained) = Self::resolution_order(Self::valid_messages(topic));
+
+		// only reporters without data, there is nothing to resolve
+		if valid_messages.is_empty() {
+			return Self::invalidate_epoch(topic, T::TokenBalance::zero());
+		}
+
+		let messages_length = valid_messages.len();
+
+		// get median 
+		let values: Vec<i64> = valid_messages.iter().map(|message| message.value).collect();
+		let median = Self::resolve_messages(topic, &valid_messages).ok_or("There are no valid messages to resolve")?;
+		let fixed_values: Vec<i128> = valid_messages.iter().map(|message| message.fixed).collect();
+		// light clients verify the resolution against the hash of the sorted values, signed for fixed point feeds
+		let dataset_hash = if Self::fixed_point() {
+			fixed_values.using_encoded(<T as system::Trait>::Hashing::hash)
+		} else {
+			values.using_encoded(<T as system::Trait>::Hashing::hash)
+		};
+		let fixed_median = Self::resolve_fixed(&fixed_values).unwrap_or_default();
+		let (distances, multipliers) = Self::reward_multipliers(topic, &valid_messages, median, fixed_median)?;
+
+		let epoch_config = Self::epoch_config(topic);
+		let tolerance = epoch_config.median_tolerance;
+
+		// the furthest rewarded value from the median, used to scale weighted rewards
+		let max_distance = distances.iter()
+			.enumerate()
+			.filter(|(i, _)| multipliers[*i].is_some())
+			.map(|(_, distance)| Self::reward_distance(*distance, tolerance))
+			.max()
+			.unwrap_or(0);
+
+		// the reward pool carried over from previous epochs of the topic is shared among the winners
+		let winners = multipliers.iter().filter(|multiplier| multiplier.is_some()).count();
+		let pool = Self::reward_pool(topic);
+		let pool_share = if winners > 0 {
+			pool.checked_div(&<T::TokenBalance as As<usize>>::sa(winners)).ok_or("pool share overflow")?
+		} else {
+			T::TokenBalance::zero()
+		};
+		// a fixed pool is split among the winners, each winner is paid RewardPerSubmission otherwise
+		let fixed_pool = epoch_config.fixed_pool_reward;
+		let full_reward = if fixed_pool.is_zero() || winners == 0 {
+			epoch_config.reward_per_submission
+		} else {
+			fixed_pool / <T::TokenBalance as As<usize>>::sa(winners)
+		};
+		let mut penalties = T::TokenBalance::zero();
+		let mut slashed = Vec::new();
+
+		// reveals after this block get a reduced reward
+		let copy_guard_block = if Self::copy_guard_bps() > 0 {
+			Self::round_one_end(topic, epoch_start).and_then(|round_one_end| round_one_end.checked_add(&Self::copy_guard_threshold()))
+		} else {
+			None
+		};
+
+		// reward of every sorted message, none for the penalized ones
+		// reward splits saturate instead of aborting the resolution
+		let mut rewards: Vec<Option<T::TokenBalance>> = valid_messages.iter()
+			.zip(multipliers.iter().zip(distances.iter()))
+			.map(|(message, (multiplier, distance))| multiplier.map(|multiplier| {
+				let reward = Self::reward_for(full_reward, Self::reward_distance(*distance, tolerance), max_distance, epoch_config.weighted_rewards);
+				// the bonus is earned epoch by epoch while the lock runs on
+				let bonus = if Self::locked_epochs(topic, &message.owner) > 1 { Self::long_term_bonus_bps() } else { 0 };
+				let reward = Self::apply_bps(reward, multiplier.saturating_add(bonus)).saturating_add(pool_share);
+				// late reveals are suspected of copying earlier ones
+				if copy_guard_block.map_or(false, |guard| message.revealed_at > guard) {
+					Self::apply_bps(reward, 10_000 - Self::copy_guard_bps())
+				} else {
+					reward
+				}
+			}))
+			.collect();
+
+		// accurate values outside of the band get the consolation reward instead of the penalty
+		let consolation = Self::consolation_reward();
+		if winners > 0 && !consolation.is_zero() {
+			for (reward, distance) in rewards.iter_mut().zip(distances.iter()) {
+				if reward.is_none() && *distance <= epoch_config.consolation_tolerance {
+					*reward = Some(consolation);
+				}
+			}
+		}
+
+		// the rewards of a fixed pool add up to it and the pool share whatever their tiers, weights and bonuses,
+		// the rounding dust goes to the lowest rewarded value
+		let total_reward = Self::total_reward(&rewards);
+		if !fixed_pool.is_zero() && !total_reward.is_zero() {
+			let target = fixed_pool.saturating_add(pool_share.checked_mul(&<T::TokenBalance as As<usize>>::sa(winners)).unwrap_or(pool));
+			Self::scale_rewards(&mut rewards, total_reward, target);
+			let dust = target.saturating_sub(Self::total_reward(&rewards));
+			if let Some(reward) = rewards.iter_mut().filter_map(|reward| reward.as_mut()).next() {
+				*reward = reward.saturating_add(dust);
+			}
+		}
+
+		// the first revealer bonus is paid from token_base on top of the rewards
+		let bonus = Self::first_revealer_bonus();
+		let first_revealer_bonus = Self::first_revealer(topic)
+			.filter(|_| !bonus.is_zero())
+			.map(|first_revealer| (first_revealer, bonus));
+		let token_base = Self::token_base();
+		let balance = <token::Module<T>>::balance_of(&token_base);
+		let bonus = first_revealer_bonus.as_ref().map_or(T::TokenBalance::zero(), |(_, bonus)| *bonus);
+
+		// the total payout of the epoch is capped at MaxEpochPayout, rewards are scaled down proportionally
+		let max_payout = Self::max_epoch_payout();
+		let total_payout = Self::total_reward(&rewards);
+		if !max_payout.is_zero() && total_payout > max_payout {
+			Self::scale_rewards(&mut rewards, total_payout, max_payout);
+		}
+
+		// so is a payout token_base can not fund, the deposits are settled as usual
+		// the rewards escrowed for earlier epochs are owed already, the first revealer bonus is set
+		// aside when token_base can fund it
+		let total_payout = Self::total_reward(&rewards);
+		let balance = balance.saturating_sub(Self::escrowed_rewards());
+		let funds = balance.checked_sub(&bonus).unwrap_or(balance);
+		let underfunded = total_payout > funds;
+		if underfunded {
+			Self::scale_rewards(&mut rewards, total_payout, funds);
+		}
+
+		let escrow_rewards = Self::escrow_rewards();
+		let accrue_earnings = Self::accrue_earnings();
+
+		// every unlock and transfer is checked before anything is written, rewards scaled down to zero
+		// are not transferred, escrowed and accrued rewards are only paid out when claimed
+		let locks: Vec<(T::Hash, T::TokenBalance)> = abstained.iter().chain(valid_messages.iter())
+			.map(|message| (message.hash, message.deposit))
+			.collect();
+		let mut transfers: Vec<(T::AccountId, T::TokenBalance)> = Vec::new();
+		for (message, reward) in valid_messages.iter().zip(rewards.iter()) {
+			let reward = match reward {
+				Some(reward) if !reward.is_zero() => *reward,
+				_ => continue,
+			};
+			if escrow_rewards {
+				continue;
+			}
+			if accrue_earnings {
+				Self::accrued_earnings(&message.owner).checked_add(&reward).ok_or("accrued earnings overflow")?;
+			} else {
+				transfers.push((message.owner.clone(), reward));
+			}
+		}
+		if let Some((first_revealer, bonus)) = first_revealer_bonus.clone() {
+			match transfers.iter_mut().find(|(who, _)| *who == first_revealer) {
+				Some((_, amount)) => *amount = amount.saturating_add(bonus),
+				None => transfers.push((first_revealer, bonus)),
+			}
+		}
+		let claim_deadline = block_number.checked_add(&Self::claim_period()).ok_or("claim deadline overflow")?;
+		Self::ensure_settleable(topic, &locks, &transfers)?;
+
+		// every winner is paid the reward assets right away, whether its reward is escrowed or accrued
+		let bundle_winners: Vec<T::AccountId> = valid_messages.iter()
+			.zip(multipliers.iter())
+			.filter(|(_, multiplier)| multiplier.is_some())
+			.map(|(message, _)| message.owner.clone())
+			.collect();
+		Self::ensure_bundles_fundable(topic, &epoch_config.reward_bundles, bundle_winners.len())?;
+
+		if underfunded {
+			// emit event that token_base could not fund the full rewards
+			Self::deposit_event(RawEvent::InsufficientRewardFunds(total_payout, funds));
+		}
+
+		for (message, distance) in valid_messages.iter().zip(distances.iter()) {
+			Self::record_accuracy(topic, &message.owner, *distance);
+		}
+
+		// messages without data get their deposit back without reward or penalty
+		for message in abstained {
+			<RevealedSet<T>>::remove(&topic, &(message.owner.clone(), epoch_start));
+			Self::return_deposit(topic, message.owner.clone(), message.deposit, message.hash)?;
+
+			// emit event that the deposit was settled without reward or penalty
+			Self::deposit_event(RawEvent::DepositReturned(message.owner, message.deposit));
+		}
+		<RevealedValueCounts<T>>::remove(&topic, &0i64);
+
+		// composite feeds are resolved position by position
+		let width = Self::feed_width() as usize;
+		if width > 1 {
+			let composite: Vec<i64> = (0..width).map(|position| {
+				let mut column: Vec<i64> = valid_messages.iter()
+					.filter_map(|message| message.values.get(position).cloned())
+					.collect();
+				column.sort();
+				Self::resolve(&column).unwrap_or_default()
+			}).collect();
+			<CompositeValue<T>>::insert(topic, composite);
+		}
+
+		// the unlocks and transfers below were checked, they do not fail halfway
+		for (message, reward) in valid_messages.into_iter().zip(rewards.into_iter()) {
+			<RevealedSet<T>>::remove(&topic, &(message.owner.clone(), epoch_start));
+
+			// if inside 25 and and 75 percentile range or one of the reward tiers
+			if let Some(reward) = reward {
+				// unlock deposits
+				Self::return_deposit(topic, message.owner.clone(), message.deposit, message.hash)?;
+				Self::deposit_event(RawEvent::DepositReturned(message.owner.clone(), message.deposit));
+				Self::add_reputation(&message.owner);
+
+				// send rewards from token_base, rewards scaled down to zero are not transferred
+				if escrow_rewards {
+					Self::escrow_reward(topic, message.owner.clone(), epoch_start, reward)?;
+				} else if accrue_earnings {
+					let earnings = Self::accrued_earnings(&message.owner).checked_add(&reward).ok_or("accrued earnings overflow")?;
+					<AccruedEarnings<T>>::insert(&message.owner, earnings);
+				} else if !reward.is_zero() {
+					Self::transfer_from_base(&token_base, &message.owner, reward)?;
+				}
+
+				// emit event that the reward was paid, escrowed or accrued
+				Self::deposit_event(RawEvent::RewardPaid(message.owner, reward, message.evidence));
+			// too few messages for a reward band, nobody is rewarded or penalized
+			} else if winners == 0 {
+				Self::return_deposit(topic, message.owner.clone(), message.deposit, message.hash)?;
+				Self::deposit_event(RawEvent::DepositReturned(message.owner, message.deposit));
+			// if out of the range
+			} else {
+				let deposit = message.deposit;
+
+				// get the 99 percent of the deposit token refund 
+				let refund = match deposit.checked_mul(&T::TokenBalance::sa(99)) {
+					Some(step) => step / T::TokenBalance::sa(100),
+					// too large to scale up first, round the other way
+					None => deposit / T::TokenBalance::sa(100) * T::TokenBalance::sa(99),
+				};
+				let refund = Self::without_dust(refund);
+				let penalty = deposit.checked_sub(&refund).ok_or("penalty overflow")?;
+
+				// send back deposits after subtration of penalties
+				Self::return_deposit(topic, message.owner.clone(), refund, message.hash)?;
+				Self::deposit_event(RawEvent::DepositReturned(message.owner.clone(), refund));
+				
+				// send penalties to token_base
+				<token::Module<T>>::unlock(token_base.clone(), penalty, message.hash)?;					
+				penalties = penalties.saturating_add(penalty);
+
+				// emit event that the deposit was cut by the penalty
+				Self::deposit_event(RawEvent::PenaltyApplied(message.owner.clone(), penalty, message.evidence));
+				if slashed.len() < MAX_LAST_SLASHED {
+					slashed.push(message.owner);
+				}
+			}
+		}
+
+		// the first revealer is paid on top of the reward or penalty of its message
+		if let Some((first_revealer, bonus)) = first_revealer_bonus {
+			Self::transfer_from_base(&token_base, &first_revealer, bonus)?;
+		}
+
+		for winner in bundle_winners {
+			for (asset, amount) in epoch_config.reward_bundles.iter() {
+				<token::Module<T>>::move_asset(*asset, token_base.clone(), winner.clone(), *amount)?;
+
+				// emit event that a reward asset was paid
+				Self::deposit_event(RawEvent::AssetRewardPaid(winner.clone(), *asset, *amount));
+			}
+		}
+
+		// recycle a share of the penalties into the reward pool of the next epoch
+		let paid_out = pool_share.checked_mul(&<T::TokenBalance as As<usize>>::sa(winners)).unwrap_or(pool);
+		let recycled = Self::apply_bps(penalties, epoch_config.penalty_recycle_bps);
+		let next_pool = pool.saturating_sub(paid_out).saturating_add(recycled);
+		<RewardPool<T>>::insert(topic, next_pool);
+
+		let lifetime_penalties = Self::lifetime_penalties().saturating_add(penalties);
+		<LifetimePenalties<T>>::put(lifetime_penalties);
+
+		// the records of the last resolution and the consumers follow the default topic
+		if topic == DEFAULT_TOPIC {
+			<LastSlashed<T>>::put(slashed);
+
+			let resolution = Resolution {
+				value: median,
+				epoch_start,
+				revealed: messages_length as u32,
+				winners: winners as u32,
+				deposits: Self::deposit_stats(),
+			};
+			<LastResolutionEncoded<T>>::put(resolution.encode());
+
+			// confidence in the value from the participation and the interquartile range
+			let epoch_committers = Self::committers(DEFAULT_TOPIC).iter()
+				.filter(|who| <Messages<T>>::get(&DEFAULT_TOPIC, *who).epoch == epoch_start)
+				.count();
+			let iqr = Self::distance(values[(messages_length * 3 / 4).min(messages_length - 1)], values[messages_length / 4]);
+			let confidence = Self::confidence(messages_length, messages_length + epoch_committers, median, iqr);
+			<LastConfidence<T>>::put(confidence);
+		}
+
+		// escrowed rewards of this epoch can be claimed for ClaimPeriod blocks
+		if escrow_rewards {
+			<ClaimDeadlines<T>>::insert((topic, epoch_start), claim_deadline);
+		}
+
+		//replace ValidMessages array with an empty one
+		<ValidMessages<T>>::remove(topic);
+		for value in values.iter() {
+			<RevealedValueCounts<T>>::remove(&topic, value);
+		}
+		<DistinctValueCount<T>>::remove(topic);
+
+		// the value is only written once everything else is settled
+		let value = Self::smooth(topic, median);
+		<Value<T>>::insert(topic, value);
+		if topic != DEFAULT_TOPIC {
+			// emit event that new value of the topic is set
+			Self::deposit_event(RawEvent::TopicValueSet(topic, epoch_start, value));
+
+			Self::start_epoch(topic);
+
+			return Ok(());
+		}
+
+		<ValueSetBlock<T>>::put(block_number);
+		<ValueCarried<T>>::put(false);
+		<LastDatasetHash<T>>::put(dataset_hash);
+		<ValueVersion<T>>::mutate(|version| *version = version.wrapping_add(1));
+		Self::record_winners(epoch_start, winners as u32);
+
+		// the plain values of a fixed point feed are all zero, its signed median is published instead
+		if Self::fixed_point() {
+			<FixedValue<T>>::put(fixed_median);
+			<LastSortedFixedValues<T>>::put(fixed_values.iter().take(MAX_LAST_SORTED_VALUES).cloned().collect::<Vec<i128>>());
+			Self::record_fixed_value(epoch_start, fixed_median);
+
+			// emit event that a new signed value is set
+			Self::deposit_event(RawEvent::NewFixedValueSet(epoch_start, fixed_median));
+
+			for consumer in Self::consumers().iter() {
+				T::OnNewValue::on_new_fixed_value(consumer, fixed_median);
+			}
+		} else {
+			<LastSortedValues<T>>::put(values.iter().take(MAX_LAST_SORTED_VALUES).cloned().collect::<Vec<i64>>());
+			Self::record_value(epoch_start, value);
+
+			// Emit event that new value is being set
+			Self::deposit_event(RawEvent::NewValueSet(epoch_start, value));
+
+			for consumer in Self::consumers().iter() {
+				T::OnNewValue::on_new_value(consumer, value);
+			}
+		}
+
+		Self::start_epoch(topic);
+
+		Ok(())
+	}
+
+	// fold the distance of a resolved message of `topic` into the running average of its owner
+	fn record_accuracy(topic: TopicId, owner: &T::AccountId, distance: u64) {
+		<Accuracies<T>>::mutate((topic, owner.clone()), |accuracy| {
+			let epochs = accuracy.epochs as u128;
+			let total = (accuracy.average_distance as u128) * epochs + distance as u128;
+			accuracy.average_distance = (total / (epochs + 1)) as u64;
+			accuracy.epochs = accuracy.epochs.saturating_add(1);
+		});
+	}
+
+	// count a rewarded epoch in the reputation of `who`, keeping the accounts sorted for paging
+	fn add_reputation(who: &T::AccountId) {
+		<Reputation<T>>::mutate(who, |reputation| *reputation = reputation.saturating_add(1));
+		<ReputationAccounts<T>>::mutate(|accounts| {
+			if let Err(position) = accounts.binary_search(who) {
+				accounts.insert(position, who.clone());
+			}
+		});
+	}
+
+	// append a resolved value to the history, pruning the oldest entries beyond MaxHistoryLength
+	fn record_value(epoch_start: T::BlockNumber, value: i64) {
+		let max_length = Self::max_history_length() as usize;
+		let mut history = Self::value_history();
+		history.push((epoch_start, value));
+		if history.len() > max_length {
+			let excess = history.len() - max_length;
+			history.drain(..excess);
+		}
+
+		<ValueHistory<T>>::put(history);
+	}
+
+	// append a signed value resolved by a fixed point feed to its history, pruned like ValueHistory
+	fn record_fixed_value(epoch_start: T::BlockNumber, value: i128) {
+		let max_length = Self::max_history_length() as usize;
+		let mut history = Self::fixed_value_history();
+		history.push((epoch_start, value));
+		if history.len() > max_length {
+			let excess = history.len() - max_length;
+			history.drain(..excess);
+		}
+
+		<FixedValueHistory<T>>::put(history);
+	}
+
+	// append the winner count of a resolved epoch to the history, pruned like ValueHistory
+	fn record_winners(epoch_start: T::BlockNumber, winners: u32) {
+		let max_length = Self::max_history_length() as usize;
+		let mut history = Self::winner_history();
+		history.push((epoch_start, winners));
+		if history.len() > max_length {
+			let excess = history.len() - max_length;
+			history.drain(..excess);
+		}
+
+		<WinnerHistory<T>>::put(history);
+	}
+
+	// blend of the crowd median and the previous value of the topic, `SmoothingBps` being the weight of the previous value
+	// the first resolution has no previous value and takes the median as is
+	fn smooth(topic: TopicId, median: i64) -> i64 {
+		let smoothing = Self::smoothing_bps() as i128;
+		let resolved = if topic == DEFAULT_TOPIC { Self::value_version() > 0 } else { <Value<T>>::exists(topic) };
+		if smoothing == 0 || !resolved {
+			return median;
+		}
+
+		let blended = (median as i128) * (10_000 - smoothing) + (Self::value(topic) as i128) * smoothing;
+		(blended / 10_000) as i64
+	}
+
+	// tiers are given innermost first with strictly increasing edges within 10000 basis points
+	fn validate_reward_tiers(tiers: &[RewardTier]) -> Result {
+		ensure!(tiers.len() <= MAX_REWARD_TIERS, "Too many reward tiers");
+		ensure!(tiers.iter().all(|tier| tier.edge_bps > 0 && tier.edge_bps <= 10_000), "Tier edges should be within 10000 basis points");
+		ensure!(tiers.windows(2).all(|pair| pair[0].edge_bps < pair[1].edge_bps), "Tier edges should be strictly increasing");
+
+		Ok(())
+	}
+
+	// refund the unrevealed commitment of an account and remove it, returns the refunded amount
+	// a commitment is refunded in full during the hash submission round, can not be released during
+	// its reveal round and forfeits UnrevealedForfeitBps of the deposit once it missed the reveal round
+	fn release_commitment(topic: TopicId, who: &T::AccountId) -> result::Result<T::TokenBalance, &'static str> {
+		let message = <Messages<T>>::get(&topic, who);
+		let refund = Self::withdrawal_refund(topic, &message)?;
+		let forfeit = message.deposit.checked_sub(&refund).ok_or("forfeit overflow")?;
+
+		// a running lock keeps the refund in the standing deposit
+		if Self::locked_epochs(topic, who) > 0 {
+			Self::add_standing_deposit(message.owner.clone(), refund, message.hash)?;
+		} else {
+			<token::Module<T>>::unlock(message.owner.clone(), refund, message.hash)?;
+		}
+		Self::pay_forfeit(topic, message.owner, forfeit, message.hash)?;
+		Self::remove_commitment(topic, who);
+
+		Ok(refund)
+	}
+
+	// amount a pending commitment is refunded if withdrawn now, without the forfeit
+	// if it missed its reveal round
+	fn withdrawal_refund(topic: TopicId, message: &Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>) -> result::Result<T::TokenBalance, &'static str> {
+		ensure!(message.status == 1, "Message status should be 1");
+
+		let epoch_start = Self::epoch_start(topic);
+		let block_number = <system::Module<T>>::block_number();
+		let round_one_end = Self::round_one_end(topic, epoch_start).ok_or("round_one_end overflow")?;
+		let reveal_deadline = Self::reveal_deadline(topic, epoch_start).ok_or("reveal_deadline overflow")?;
+		let in_reveal_round = message.epoch == epoch_start && block_number >= round_one_end && block_number < reveal_deadline;
+		// a commitment that can not be revealed anymore is refunded in full
+		ensure!(!in_reveal_round || Self::submissions_full(topic), "The reveal round is open, reveal the value or withdraw after the deadline");
+
+		// a commitment of an epoch root started over or settled early never had its whole reveal round
+		if message.epoch == epoch_start && block_number >= reveal_deadline {
+			Self::unrevealed_refund(message.deposit)
+		} else {
+			Ok(message.deposit)
+		}
+	}
+
+	// whether the epoch of `topic` accepted MaxSubmissions values already
+	fn submissions_full(topic: TopicId) -> bool {
+		let max_submissions = Self::max_submissions();
+		max_submissions > 0 && Self::valid_messages(topic).len() >= max_submissions as usize
+	}
+
+	// delete the message from the map, a running lock is left in place
+	fn remove_commitment(topic: TopicId, who: &T::AccountId) {
+		<Messages<T>>::remove(&topic, who);
+		Self::remove_committer(topic, who);
+	}
+
+	// the deposit of an unrevealed commitment without the UnrevealedForfeitBps forfeit
+	fn unrevealed_refund(deposit: T::TokenBalance) -> result::Result<T::TokenBalance, &'static str> {
+		let step = deposit.checked_mul(&<T::TokenBalance as As<u64>>::sa(Self::unrevealed_forfeit_bps() as u64)).ok_or("forfeit step overflow")?;
+		let forfeit = step.checked_div(&<T::TokenBalance as As<u64>>::sa(10_000)).ok_or("forfeit overflow")?;
+
+		deposit.checked_sub(&forfeit).ok_or("refund overflow")
+	}
+
+	// move the forfeit of a commitment withdrawn after missing its reveal round to the forfeit destination
+	// counted with the penalties like the forfeits of a settlement
+	fn pay_forfeit(topic: TopicId, owner: T::AccountId, forfeit: T::TokenBalance, hash: T::Hash) -> Result {
+		if !forfeit.is_zero() {
+			Self::forfeit_to_destination(forfeit, hash)?;
+			Self::record_forfeit(topic, forfeit, Self::epoch_config(topic).penalty_recycle_bps);
+
+			// emit event that the committer was penalized for not revealing
+			Self::deposit_event(RawEvent::NonRevealPenalized(owner, forfeit));
+		}
+
+		Ok(())
+	}
+
+	// count forfeited deposits with the penalties, only a forfeit unlocked to token_base can fund the rewards of `topic`
+	fn record_forfeit(topic: TopicId, amount: T::TokenBalance, recycle_bps: u32) {
+		if Self::forfeit_destination() == ForfeitDestination::Base {
+			let recycled = Self::apply_bps(amount, recycle_bps);
+			<RewardPool<T>>::mutate(topic, |pool| *pool = pool.saturating_add(recycled));
+		}
+		<LifetimePenalties<T>>::mutate(|penalties| *penalties = penalties.saturating_add(amount));
+	}
+
+	// move `amount` locked under `hash` to the forfeit destination
+	fn forfeit_to_destination(amount: T::TokenBalance, hash: T::Hash) -> Result {
+		match Self::forfeit_destination() {
+			ForfeitDestination::Base => <token::Module<T>>::unlock(Self::token_base(), amount, hash),
+			ForfeitDestination::Treasury(treasury) => <token::Module<T>>::unlock(treasury, amount, hash),
+			ForfeitDestination::Burn => <token::Module<T>>::burn(amount, hash),
+		}
+	}
+
+	// settle the epoch once the reveal round closed, the commitments left unrevealed are slashed afterwards
+	fn settle_epoch(topic: TopicId) -> Result {
+		let epoch_start = Self::epoch_start(topic);
+		let recycle_bps = Self::epoch_config(topic).penalty_recycle_bps;
+		// once MaxSubmissions values were accepted the commitments left could not be revealed anymore
+		let reveals_closed = Self::submissions_full(topic);
+		// the commitments left are settled after the epoch, their deposits are checked with the others first
+		let locks: Vec<(T::Hash, T::TokenBalance)> = Self::committers(topic).iter()
+			.map(|who| <Messages<T>>::get(&topic, who))
+			.filter(|message| message.status == 1 && message.epoch == epoch_start)
+			.map(|message| (message.hash, message.deposit))
+			.collect();
+		Self::ensure_settleable(topic, &locks, &[])?;
+		Self::resolve_epoch(topic)?;
+
+		if reveals_closed {
+			Self::refund_non_revealers(topic, epoch_start)
+		} else {
+			Self::slash_non_revealers(topic, epoch_start, recycle_bps)
+		}
+	}
+
+	// the deposit of every commitment of the epoch that was not revealed is returned in full
+	fn refund_non_revealers(topic: TopicId, epoch_start: T::BlockNumber) -> Result {
+		for who in Self::committers(topic) {
+			let message = <Messages<T>>::get(&topic, &who);
+			if message.status != 1 || message.epoch != epoch_start {
+				continue;
+			}
+
+			Self::return_deposit(topic, who.clone(), message.deposit, message.hash)?;
+			Self::remove_commitment(topic, &who);
+
+			// emit event that the deposit was settled without reward or penalty
+			Self::deposit_event(RawEvent::DepositReturned(who, message.deposit));
+		}
+
+		Ok(())
+	}
+
+	// every commitment of the epoch that was not revealed forfeits UnrevealedForfeitBps of its deposit
+	// to the forfeit destination, as if it was withdrawn after the deadline, the rest is returned
+	fn slash_non_revealers(topic: TopicId, epoch_start: T::BlockNumber, recycle_bps: u32) -> Result {
+		let mut total = T::TokenBalance::zero();
+		let mut slashed = Self::last_slashed();
+		for who in Self::committers(topic) {
+			let message = <Messages<T>>::get(&topic, &who);
+			if message.status != 1 || message.epoch != epoch_start {
+				continue;
+			}
+
+			let refund = Self::unrevealed_refund(message.deposit)?;
+			let forfeit = message.deposit.checked_sub(&refund).ok_or("forfeit overflow")?;
+			if !refund.is_zero() {
+				Self::return_deposit(topic, who.clone(), refund, message.hash)?;
+
+				// emit event that the rest of the deposit was returned
+				Self::deposit_event(RawEvent::DepositReturned(who.clone(), refund));
+			}
+			Self::remove_commitment(topic, &who);
+			if forfeit.is_zero() {
+				continue;
+			}
+
+			Self::forfeit_to_destination(forfeit, message.hash)?;
+			total = total.saturating_add(forfeit);
+			if slashed.len() < MAX_LAST_SLASHED {
+				slashed.push(who.clone());
+			}
+
+			// emit event that the deposit of the committer was slashed for not revealing
+			Self::deposit_event(RawEvent::NonRevealSlashed(who, forfeit));
+		}
+		if total.is_zero() {
+			return Ok(());
+		}
+
+		Self::record_forfeit(topic, total, recycle_bps);
+		if topic == DEFAULT_TOPIC {
+			<LastSlashed<T>>::put(slashed);
+		}
+
+		Ok(())
+	}
+
+	// resolve the epoch or close it without a value
+	fn resolve_epoch(topic: TopicId) -> Result {
+		let epoch_start = Self::epoch_start(topic);
+		let reveal_deadline = Self::reveal_deadline(topic, epoch_start).ok_or("reveal_deadline overflow")?;
+		let block_number = <system::Module<T>>::block_number();
+
+		ensure!(block_number >= reveal_deadline, "It's not the time to send out the rewards yet");
+
+		// an epoch without enough revealed stake behind it does not set a value
+		let total_stake = Self::valid_messages(topic).iter()
+			.fold(T::TokenBalance::zero(), |total, message| total.saturating_add(message.deposit));
+		if total_stake < Self::min_total_stake() {
+			return Self::invalidate_epoch(topic, total_stake);
+		}
+
+		// so is an epoch where too few of the committers revealed
+		let revealed = Self::valid_messages(topic).len() as u64;
+		let committed = revealed + Self::committers(topic).iter()
+			.filter(|who| <Messages<T>>::get(&topic, *who).epoch == epoch_start)
+			.count() as u64;
+		if revealed.saturating_mul(10_000) < committed.saturating_mul(Self::min_reveal_ratio_bps() as u64) {
+			return Self::invalidate_epoch(topic, total_stake);
+		}
+
+		// and an epoch where the accounts of a single funder hold too much of the stake
+		if let Some((funder, stake)) = Self::dominant_funder(topic, total_stake) {
+			// emit event that the epoch is dominated by a single funder
+			Self::deposit_event(RawEvent::FunderDominance(funder, stake));
+
+			return Self::invalidate_epoch(topic, total_stake);
+		}
+
+		// and an epoch backed by too few distinct funders
+		let unique_funders = Self::unique_funders(topic);
+		if unique_funders < Self::min_unique_funders() {
+			// emit event that too few funders are behind the revealed values
+			Self::deposit_event(RawEvent::TooFewFunders(unique_funders));
+
+			return Self::invalidate_epoch(topic, total_stake);
+		}
+
+		Self::distribute_rewards(topic)
+	}
+
+	// fail a settlement before it writes anything if one of its unlocks or transfers from token_base
+	// would fail halfway, the deposits are unlocked from the listing of their message and the transfers
+	// are made from what token_base holds beyond the escrowed rewards, within its allowance to each account
+	fn ensure_settleable(topic: TopicId, locks: &[(T::Hash, T::TokenBalance)], transfers: &[(T::AccountId, T::TokenBalance)]) -> Result {
+		let mut unlocks: Vec<(T::Hash, T::TokenBalance)> = Vec::new();
+		for (hash, amount) in locks.iter() {
+			match unlocks.iter_mut().find(|(listing, _)| listing == hash) {
+				Some((_, total)) => *total = total.saturating_add(*amount),
+				None => unlocks.push((*hash, *amount)),
+			}
+		}
+		for (hash, amount) in unlocks {
+			let locked = <token::Module<T>>::locked_deposits(hash);
+			if locked < amount {
+				return Self::abort_settlement(topic, amount, locked, "A deposit of the epoch is not locked in full");
+			}
+		}
+
+		let token_base = Self::token_base();
+		let total = transfers.iter()
+			.try_fold(T::TokenBalance::zero(), |total, (_, amount)| total.checked_add(amount))
+			.ok_or("payout overflow")?;
+		let funds = <token::Module<T>>::balance_of(&token_base).saturating_sub(Self::escrowed_rewards());
+		if total > funds {
+			return Self::abort_settlement(topic, total, funds, "token_base can not fund the payouts of the epoch");
+		}
+		for (who, amount) in transfers.iter() {
+			let allowance = <token::Module<T>>::allowance((token_base.clone(), who.clone()));
+			if allowance < *amount {
+				return Self::abort_settlement(topic, *amount, allowance, "token_base did not approve the payout of an account");
+			}
+		}
+
+		Ok(())
+	}
+
+	// fail a settlement before it writes anything if token_base does not hold every reward asset for all winners
+	fn ensure_bundles_fundable(topic: TopicId, bundles: &[(token::AssetId, T::TokenBalance)], winners: usize) -> Result {
+		let token_base = Self::token_base();
+		for (asset, amount) in bundles.iter() {
+			let required = amount.checked_mul(&<T::TokenBalance as As<usize>>::sa(winners)).ok_or("reward asset overflow")?;
+			let available = <token::Module<T>>::asset_balance_of((*asset, token_base.clone()));
+			if available < required {
+				return Self::abort_settlement(topic, required, available, "token_base can not fund the reward assets of the epoch");
+			}
+		}
+
+		Ok(())
+	}
+
+	// give up settling the epoch before anything is settled, it stays open to be settled again
+	fn abort_settlement(topic: TopicId, required: T::TokenBalance, available: T::TokenBalance, reason: &'static str) -> Result {
+		// emit event that the settlement was aborted
+		Self::deposit_event(RawEvent::SettlementAborted(topic, required, available));
+
+		Err(reason)
+	}
+
+	// transfer `amount` from token_base with its allowance to the account
+	fn transfer_from_base(token_base: &T::AccountId, who: &T::AccountId, amount: T::TokenBalance) -> Result {
+		let origin = system::RawOrigin::Root.into();
+		<token::Module<T>>::transfer_from(origin, token_base.clone(), who.clone(), amount)
+	}
+
+	// transfer `amount` from token_base once the epoch is settled, a transfer beyond its balance or its
+	// allowance to the account is skipped rather than failing the settled call
+	fn pay_from_base(token_base: &T::AccountId, who: &T::AccountId, amount: T::TokenBalance) -> result::Result<bool, &'static str> {
+		let allowance = <token::Module<T>>::allowance((token_base.clone(), who.clone()));
+		if allowance < amount || <token::Module<T>>::balance_of(token_base) < amount {
+			// emit event that the transfer was skipped
+			Self::deposit_event(RawEvent::PayoutSkipped(who.clone(), amount));
+			return Ok(false);
+		}
+
+		Self::transfer_from_base(token_base, who, amount)?;
+
+		Ok(true)
+	}
+
+	// number of distinct funders of the accounts that revealed, an account without a funder being its own
+	fn unique_funders(topic: TopicId) -> u32 {
+		let mut funders: Vec<T::AccountId> = Vec::new();
+		for message in Self::valid_messages(topic) {
+			let funder = Self::funder(&message.owner).unwrap_or(message.owner);
+			if !funders.contains(&funder) {
+				funders.push(funder);
+			}
+		}
+
+		funders.len() as u32
+	}
+
+	// a funder whose accounts revealed more than MaxFunderShareBps of the total stake, with its stake
+	fn dominant_funder(topic: TopicId, total_stake: T::TokenBalance) -> Option<(T::AccountId, T::TokenBalance)> {
+		let max_share = Self::max_funder_share_bps();
+		if max_share == 0 {
+			return None;
+		}
+
+		let mut stakes: Vec<(T::AccountId, T::TokenBalance)> = Vec::new();
+		for message in Self::valid_messages(topic) {
+			if let Some(funder) = Self::funder(&message.owner) {
+				match stakes.iter_mut().find(|(known, _)| *known == funder) {
+					Some((_, stake)) => *stake = stake.saturating_add(message.deposit),
+					None => stakes.push((funder, message.deposit)),
+				}
+			}
+		}
+
+		let limit = Self::apply_bps(total_stake, max_share);
+		stakes.into_iter().find(|(_, stake)| *stake > limit)
+	}
+
+	// close the epoch without setting a value, revealed deposits are refunded in full
+	fn invalidate_epoch(topic: TopicId, total_stake: T::TokenBalance) -> Result {
+		let epoch_start = Self::epoch_start(topic);
+		let locks: Vec<(T::Hash, T::TokenBalance)> = Self::valid_messages(topic).iter()
+			.map(|message| (message.hash, message.deposit))
+			.collect();
+		Self::ensure_settleable(topic, &locks, &[])?;
+
+		for message in Self::valid_messages(topic) {
+			<RevealedValueCounts<T>>::remove(&topic, &message.value);
+			<RevealedSet<T>>::remove(&topic, &(message.owner.clone(), epoch_start));
+			Self::return_deposit(topic, message.owner, message.deposit, message.hash)?;
+		}
+		<ValidMessages<T>>::remove(topic);
+		<DistinctValueCount<T>>::remove(topic);
+
+		// emit event that the epoch was closed without a value
+		Self::deposit_event(RawEvent::EpochInvalidated(epoch_start, total_stake));
+
+		// the value of the previous resolution stays in place, flagged as carried forward
+		if topic == DEFAULT_TOPIC {
+			let value = Self::value(DEFAULT_TOPIC);
+			<Value<T>>::insert(DEFAULT_TOPIC, value);
+			<ValueCarried<T>>::put(true);
+			<LastSlashed<T>>::kill();
+
+			// emit event that the previous value was carried forward
+			Self::deposit_event(RawEvent::ValueCarriedForward(value));
+		}
+
+		Self::start_epoch(topic);
+
+		Ok(())
+	}
+
+	// whether `sender` can commit `deposit` now, pending commitments aside
+	fn ensure_can_commit(topic: TopicId, sender: &T::AccountId, deposit: T::TokenBalance) -> Result {
+		ensure!(!Self::halted(), "The oracle is halted");
+		ensure!(!Self::commits_paused(), "New commitments are paused");
+		// rewards and penalties would flow back into the account paying them
+		ensure!(*sender != Self::token_base(), "The token_base account can not participate");
+		Self::ensure_deposit_bounds(deposit)?;
+
+		// deadline for hash submission HashSubmissionBlocks after the epoch start
+		let deadline = Self::round_one_end(topic, Self::epoch_start(topic)).ok_or("Epoch_start Overflow")?;
+
+		ensure!(<system::Module<T>>::block_number() < deadline, "The deadline for hash submission is passed, try next epoch");
+
+		Ok(())
+	}
+
+	// encoded length of a composite reveal of `width` values, the length prefix
+	// of at most MAX_FEED_WIDTH values takes a single byte
+	fn composite_encoded_len(width: u32) -> u32 {
+		1 + 8 * width
+	}
+
+	// whether `deposit` is between the minimal and the maximal deposit, zero lifts the maximum
+	fn ensure_deposit_bounds(deposit: T::TokenBalance) -> Result {
+		ensure!(deposit >= Self::min_deposit(), "The deposit is not high enough");
+		let max_deposit = Self::max_deposit();
+		ensure!(max_deposit.is_zero() || deposit <= max_deposit, "The deposit exceeds the maximal deposit");
+
+		Ok(())
+	}
+
+	// validate a commitment, lock its deposit and add it to the message list
+	fn commit(topic: TopicId, sender: T::AccountId, hash: T::Hash, deposit: T::TokenBalance, lock_duration: u32) -> Result {
+		ensure!(!<Messages<T>>::exists(&topic, &sender), "There is a submission made by the message sender");		
+		Self::ensure_can_commit(topic, &sender, deposit)?;
+		
+		let epoch_start = Self::epoch_start(topic);
+		
+		// take the deposit from the standing deposit first and lock the rest from the balance
+		let standing = Self::standing_deposit(&sender);
+		let from_standing = if standing < deposit { standing } else { deposit };
+		let from_balance = deposit.checked_sub(&from_standing).ok_or("deposit overflow")?;
+
+		if !from_balance.is_zero() {
+			if let Err(e) = <token::Module<T>>::lock(sender.clone(), from_balance, hash.clone()) {
+				// emit event that the deposit could not be locked
+				Self::deposit_event(RawEvent::LockFailed(sender, from_balance));
+				return Err(e);
+			}
+		}
+		if !from_standing.is_zero() {
+			<token::Module<T>>::relock(from_standing, Self::rollover_key(&sender), hash.clone())?;
+			<StandingDeposits<T>>::insert(&sender, standing - from_standing);
+		}
+		
+		// a longer lock extends the running one, it counts down every epoch of the topic whether the account takes part or not
+		if lock_duration > 1 {
+			let lock_end = Self::epoch_index(topic).saturating_add(lock_duration as u64);
+			if lock_end > Self::lock_end((topic, sender.clone())) {
+				<LockEnds<T>>::insert((topic, sender.clone()), lock_end);
+			}
+		}
+
+		// compose a message and add to the message list
+		let message = Message{
+			owner: sender.clone(),
+			status: 1, 
+			hash: hash, 
+			value: 0,
+			values: Vec::new(),
+			fixed: 0,
+			deposit: deposit.clone(),
+			epoch: epoch_start,
+			revealed_at: T::BlockNumber::zero(),
+			// a running lock carries over to the next commitment
+			lock_duration: Self::locked_epochs(topic, &sender).max(1),
+			evidence: T::Hash::default(),
+			delegates: Vec::new(),
+			threshold: 0,
+			approvals: Vec::new(),
+		};
+		<Messages<T>>::insert(&topic, &sender, &message);
+		<Committers<T>>::mutate(topic, |committers| committers.push(sender.clone()));
+		Self::snapshot_config(topic);
+
+		// emit event that the hash was submitted
+		Self::deposit_event(RawEvent::HashSubmitted(sender, deposit));
+
+		Ok(())
+	}
+
+	// number of messages send_rewards goes through on `topic`, the revealed values it sorts and pays
+	// and the commitments it scans for the ones left unrevealed
+	pub fn participant_count(topic: TopicId) -> u32 {
+		(Self::valid_messages(topic).len() + Self::committers(topic).len()) as u32
+	}
+
+	// weight of send_rewards settling `participants` messages, the O(n log n) sort of the values and the
+	// O(n) unlocks and transfers, for a #[weight] annotation to take once the runtime supports them
+	pub fn send_rewards_weight(participants: u32) -> u64 {
+		let participants = participants as u64;
+		let log_participants = (64 - participants.leading_zeros()) as u64;
+
+		SEND_REWARDS_BASE_WEIGHT
+			.saturating_add(participants.saturating_mul(log_participants).saturating_mul(COMPARE_WEIGHT))
+			.saturating_add(participants.saturating_mul(SEND_REWARDS_PARTICIPANT_WEIGHT))
+	}
+
+	// whether `block_number` is in the value submission round of the epoch of `topic` starting at `epoch_start`
+	// and the round still takes submissions
+	fn ensure_reveal_round(topic: TopicId, epoch_start: T::BlockNumber, block_number: T::BlockNumber) -> Result {
+		// the end of the value submission round
+		let round_one_end = Self::round_one_end(topic, epoch_start).ok_or("Round_one_end overflow")?;
+		
+		// the period for value submission lasts ValueSubmissionBlocks after the end of the hash submission round
+		let deadline = Self::reveal_deadline(topic, epoch_start).ok_or("Deadline verflow")?;
+
+		ensure!(block_number > round_one_end, "Hash submission round did not end yet");
+		ensure!(block_number < deadline, "The deadline for value submission is passed, please withdraw deposit");
+		ensure!(!Self::submissions_full(topic), "The maximum number of submissions is reached, please withdraw deposit");
+
+		Ok(())
+	}
+
+	// the checks of submit_hash and submit_value that read a few storage values only, the transaction
+	// pool runs them so a call failing them, e.g. after the deadline, is refused before it is charged
+	// the dispatched call runs them again, other calls pass
+	pub fn precheck_submission(call: &Call<T>, who: &T::AccountId) -> Result {
+		match call {
+			Call::submit_hash(topic, _, deposit) => {
+				ensure!(Self::topic_exists(*topic), "The topic does not exist");
+				ensure!(!<Messages<T>>::exists(topic, who), "There is a submission made by the message sender");
+
+				Self::ensure_can_commit(*topic, who, *deposit)
+			},
+			Call::submit_value(topic, _, _) => {
+				ensure!(Self::topic_exists(*topic), "The topic does not exist");
+				ensure!(!Self::halted(), "The oracle is halted");
+				ensure!(<Messages<T>>::exists(topic, who), "Message hash was not submitted");
+
+				let message = <Messages<T>>::get(topic, who);
+				ensure!(message.status == 1, "Message status should be 1");
+				// a stale commitment is refunded by the call
+				let epoch_start = Self::epoch_start(*topic);
+				if message.epoch != epoch_start {
+					return Ok(());
+				}
+
+				Self::ensure_reveal_round(*topic, epoch_start, <system::Module<T>>::block_number())
+			},
+			_ => Ok(()),
+		}
+	}
+
+	// validate a reveal and move the message to the list of valid messages
+	fn reveal(topic: TopicId, sender: T::AccountId, value: i64, values: Vec<i64>, fixed: i128, salt: T::Hash, evidence: T::Hash) -> Result {
+		ensure!(!Self::halted(), "The oracle is halted");
+		ensure!(<Messages<T>>::exists(&topic, &sender), "Message hash was not submitted");
+		let encoded_len = if Self::fixed_point() {
+			fixed.using_encoded(|encoded| encoded.len())
+		} else if values.is_empty() {
+			value.using_encoded(|encoded| encoded.len())
+		} else {
+			values.using_encoded(|encoded| encoded.len())
+		} as u32;
+		ensure!(encoded_len <= Self::max_reveal_encoded_len(), "The revealed value is too large");
+		
+		let epoch_start = Self::epoch_start(topic);
+		let block_number = <system::Module<T>>::block_number();
+
+		let mut message = <Messages<T>>::get(&topic, &sender);
+		ensure!(message.status == 1, "Message status should be 1");
+
+		// a commitment of a previous epoch can not be revealed anymore, refund it instead
+		// it only outlived its epoch because root started over or settled it early
+		if message.epoch != epoch_start {
+			<token::Module<T>>::unlock(message.owner, message.deposit, message.hash)?;
+			Self::remove_commitment(topic, &sender);
+
+			// emit event that the stale commitment was refunded
+			Self::deposit_event(RawEvent::StaleCommitmentRefunded(sender, message.deposit));
+
+			return Ok(());
+		}
+
+		Self::ensure_reveal_round(topic, epoch_start, block_number)?;
+
+		// compare the hash of account id, revealed value and salt with the hash being submitted
+		let random_hash = if Self::fixed_point() {
+			<T as system::Trait>::Hashing::hash_of(&(sender.clone(), fixed, salt))
+		} else if !values.is_empty() {
+			<T as system::Trait>::Hashing::hash_of(&(sender.clone(), values.clone(), salt))
+		} else {
+			<T as system::Trait>::Hashing::hash_of(&(sender.clone(), value, salt))
+		};
+		ensure!(random_hash == message.hash, "The revealed value does not match the committed hash");
+
+		// update message info and add to the list of valid messages
+		message.value = value.clone();
+		message.values = values;
+		message.fixed = fixed;
+		message.evidence = evidence;
+		message.status = 2;
+
+		Self::accept_value(topic, message, block_number);
+
+		// delete message from the map
+		Self::remove_commitment(topic, &sender);
+
+		Ok(())
+	}
+
+	// add a revealed message to the list of valid messages
+	fn accept_value(topic: TopicId, mut message: Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>, block_number: T::BlockNumber) {
+		message.revealed_at = block_number;
+		let sender = message.owner.clone();
+		let value = message.value;
+		let epoch_start = message.epoch;
+		let evidence = message.evidence;
+
+		let mut valid_messages = Self::valid_messages(topic);
+		valid_messages.push(message);
+
+		<ValidMessages<T>>::insert(topic, valid_messages);
+
+		let reveals_of_value = Self::revealed_value_count(topic, value);
+		if reveals_of_value == 0 {
+			<DistinctValueCount<T>>::mutate(topic, |count| *count = count.saturating_add(1));
+		}
+		<RevealedValueCounts<T>>::insert(&topic, &value, &reveals_of_value.saturating_add(1));
+
+		<RevealedSet<T>>::insert(&topic, &(sender.clone(), epoch_start), &true);
+		if Self::first_revealer(topic).is_none() {
+			<FirstRevealer<T>>::insert(topic, sender.clone());
+		}
+
+		<LastRevealBlock<T>>::insert(topic, block_number);
+		<Stalling<T>>::insert(topic, false);
+
+		// emit event that the value submission was accepted
+		Self::deposit_event(RawEvent::ValueSubmissionAccepted(sender.clone(), value));
+		if evidence != T::Hash::default() {
+			<RevealEvidence<T>>::insert((sender.clone(), epoch_start), evidence);
+
+			// emit event that the value is backed by evidence
+			Self::deposit_event(RawEvent::EvidenceAttached(sender, evidence));
+		}
+	}
+
+	// deposit normalized to a number of minimal deposits, the signal in stake signal mode
+	// saturated at the largest value rather than wrapped for balances wider than the values
+	fn stake_signal(deposit: T::TokenBalance) -> i64 {
+		let unit = Self::min_deposit();
+		let normalized = if unit.is_zero() { deposit } else { deposit / unit };
+
+		if normalized >= <T::TokenBalance as As<u64>>::sa(i64::max_value() as u64) {
+			return i64::max_value();
+		}
+		<T::TokenBalance as As<u64>>::as_(normalized) as i64
+	}
+
+	// end of the hash submission round of the epoch of `topic` starting at `epoch_start`
+	fn round_one_end(topic: TopicId, epoch_start: T::BlockNumber) -> Option<T::BlockNumber> {
+		epoch_start.checked_add(&Self::hash_submission_blocks(topic))
+	}
+
+	// end of the value submission round of the epoch of `topic` starting at `epoch_start`, the current one
+	fn reveal_deadline(topic: TopicId, epoch_start: T::BlockNumber) -> Option<T::BlockNumber> {
+		Self::round_one_end(topic, epoch_start)?
+			.checked_add(&Self::value_submission_blocks(topic))?
+			.checked_add(&Self::reveal_extension(topic))
+	}
+
+	// page `page` of the accounts penalized in the last settled epoch, `page_size` accounts per page
+	pub fn last_slashed_page(page: u32, page_size: u32) -> Vec<T::AccountId> {
+		let start = (page as usize).saturating_mul(page_size as usize);
+
+		Self::last_slashed().into_iter().skip(start).take(page_size as usize).collect()
+	}
+
+	// up to `limit` accounts with their reputation in ascending order of the accounts, from position `start` on,
+	// at most MAX_REPUTATION_PAGE of them
+	pub fn reputation_page(start: u32, limit: u32) -> Vec<(T::AccountId, u32)> {
+		Self::reputation_accounts().into_iter()
+			.skip(start as usize)
+			.take(limit.min(MAX_REPUTATION_PAGE) as usize)
+			.map(|who| {
+				let reputation = Self::reputation(&who);
+				(who, reputation)
+			})
+			.collect()
+	}
+
+	// up to `limit` of the values sorted by the last resolution, from position `start` on
+	pub fn last_sorted_values_from(start: u32, limit: u32) -> Vec<i64> {
+		Self::last_sorted_values().into_iter().skip(start as usize).take(limit as usize).collect()
+	}
+
+	// page `page` of the winner counts of the last resolved epochs, oldest first
+	pub fn winner_history_page(page: u32, page_size: u32) -> Vec<(T::BlockNumber, u32)> {
+		let start = (page as usize).saturating_mul(page_size as usize);
+
+		Self::winner_history().into_iter().skip(start).take(page_size as usize).collect()
+	}
+
+	// number of resolved epochs kept in ValueHistory
+	pub fn history_len() -> u32 {
+		Self::value_history().len() as u32
+	}
+
+	// evidence attached to the value the account revealed in the epoch starting at `epoch`, if any
+	pub fn evidence_of(who: T::AccountId, epoch: T::BlockNumber) -> Option<T::Hash> {
+		let evidence = Self::reveal_evidence((who, epoch));
+		if evidence == T::Hash::default() { None } else { Some(evidence) }
+	}
+
+	// whether the account revealed a value in the current epoch of `topic`
+	pub fn has_revealed(topic: TopicId, who: T::AccountId) -> bool {
+		<RevealedSet<T>>::get(&topic, &(who, Self::epoch_start(topic)))
+	}
+
+	// number of reveals of `value` in the current epoch of `topic`
+	fn revealed_value_count(topic: TopicId, value: i64) -> u32 {
+		<RevealedValueCounts<T>>::get(&topic, &value)
+	}
+
+	// end of the reveal round of the current epoch, when send_rewards is allowed
+	fn resolution_block() -> T::BlockNumber {
+		Self::epoch_start(DEFAULT_TOPIC)
+			.saturating_add(Self::hash_submission_blocks(DEFAULT_TOPIC))
+			.saturating_add(Self::value_submission_blocks(DEFAULT_TOPIC))
+			.saturating_add(Self::reveal_extension(DEFAULT_TOPIC))
+	}
+
+	// blocks left until the end of the reveal round, when send_rewards is allowed
+	pub fn blocks_until_resolution() -> T::BlockNumber {
+		Self::resolution_block().saturating_sub(<system::Module<T>>::block_number())
+	}
+
+	// whether the resolution block of the current epoch passed without the epoch being settled,
+	// the current value is stale until it is
+	pub fn resolution_overdue() -> bool {
+		Self::epoch_active(DEFAULT_TOPIC) && <system::Module<T>>::block_number() > Self::resolution_block()
+	}
+
+	// phase of the current epoch and the blocks left until it ends, zero once the epoch can be settled
+	// the last block of the hash submission round takes no commitments, but reveals only open after it
+	pub fn epoch_phase() -> (EpochPhase, T::BlockNumber) {
+		let epoch_start = Self::epoch_start(DEFAULT_TOPIC);
+		let block_number = <system::Module<T>>::block_number();
+		let round_one_end = epoch_start.saturating_add(Self::hash_submission_blocks(DEFAULT_TOPIC));
+		let reveal_deadline = round_one_end
+			.saturating_add(Self::value_submission_blocks(DEFAULT_TOPIC))
+			.saturating_add(Self::reveal_extension(DEFAULT_TOPIC));
+
+		let (phase, phase_end) = if !Self::epoch_active(DEFAULT_TOPIC) {
+			(EpochPhase::Inactive, block_number)
+		} else if block_number <= round_one_end {
+			(EpochPhase::Commit, round_one_end.saturating_add(T::BlockNumber::sa(1)))
+		} else if block_number < reveal_deadline {
+			(EpochPhase::Reveal, reveal_deadline)
+		} else {
+			(EpochPhase::Resolution, block_number)
+		};
+
+		(phase, phase_end.saturating_sub(block_number))
+	}
+
+	// phase, participation and locked deposits of the current epoch
+	pub fn epoch_state() -> EpochState<T::BlockNumber, T::TokenBalance> {
+		let epoch_start = Self::epoch_start(DEFAULT_TOPIC);
+		let (phase, blocks_remaining) = Self::epoch_phase();
+
+		let revealed = Self::valid_messages(DEFAULT_TOPIC).len() as u32;
+		let pending = Self::committers(DEFAULT_TOPIC).iter()
+			.filter(|who| <Messages<T>>::get(&DEFAULT_TOPIC, *who).epoch == epoch_start)
+			.count() as u32;
+
+		EpochState {
+			epoch_start,
+			phase,
+			committed: pending + revealed,
+			revealed,
+			total_locked: Self::deposit_stats().total,
+			blocks_remaining,
+		}
+	}
+
+	// value resolved from the submitted values sorted in ascending order
+	// the mean of the two middle values for an even number of values, rounded down
+	fn resolve(sorted_values: &[i64]) -> Option<i64> {
+		let len = sorted_values.len();
+		let upper = *sorted_values.get(len / 2)?;
+		if len % 2 == 1 {
+			return Some(upper);
+		}
+
+		Some(Self::midpoint(sorted_values[len / 2 - 1], upper))
+	}
+
+	// halfway from `lower` to `upper`, rounded down, without overflowing for values of opposite signs
+	fn midpoint(lower: i64, upper: i64) -> i64 {
+		let spread = upper as i128 - lower as i128;
+
+		(lower as i128 + spread / 2) as i64
+	}
+
+	// value at the middle of the total deposit of the values sorted in ascending order,
+	// halfway to the next value if exactly half of the deposit is on either side
+	fn resolve_weighted(sorted_values: &[(i64, T::TokenBalance)]) -> Option<i64> {
+		let total = sorted_values.iter().fold(T::TokenBalance::zero(), |total, (_, deposit)| total.saturating_add(*deposit));
+
+		let mut below = T::TokenBalance::zero();
+		for (i, &(value, deposit)) in sorted_values.iter().enumerate() {
+			below = below.saturating_add(deposit);
+			let above = total.saturating_sub(below);
+			if below > above {
+				return Some(value);
+			}
+			if below == above {
+				let upper = sorted_values.get(i + 1).map_or(value, |next| next.0);
+				return Some(Self::midpoint(value, upper));
+			}
+		}
+
+		None
+	}
+
+	// value resolved from the fixed point values sorted in ascending order, like `resolve`
+	fn resolve_fixed(sorted_values: &[i128]) -> Option<i128> {
+		let len = sorted_values.len();
+		let upper = *sorted_values.get(len / 2)?;
+		if len % 2 == 1 {
+			return Some(upper);
+		}
+
+		let lower = sorted_values[len / 2 - 1];
+		upper.checked_sub(lower).and_then(|spread| lower.checked_add(spread / 2))
+	}
+
+	// messages taking part in the resolution sorted by value, and the ones left out as having no data,
+	// zero meaning no data when IgnoreZeroValues is set
+	// ties are broken by the owner so every node settles the same winners regardless of the reveal order,
+	// fixed point values are all zero but for fixed point feeds, where the plain values are zero
+	fn resolution_order(
+		messages: Vec<Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>>,
+	) -> (Vec<Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>>, Vec<Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>>) {
+		let (mut resolved, abstained): (Vec<_>, Vec<_>) = if Self::ignore_zero_values() {
+			messages.into_iter().partition(|message| message.value != 0 || message.fixed != 0)
+		} else {
+			(messages, Vec::new())
+		};
+		resolved.sort_by(|a, b| a.fixed.cmp(&b.fixed)
+			.then_with(|| a.value.cmp(&b.value))
+			.then_with(|| a.owner.cmp(&b.owner)));
+
+		(resolved, abstained)
+	}
+
+	// median of the messages of `topic` in resolution order, weighted by their deposit in WeightedMode
+	fn resolve_messages(topic: TopicId, sorted: &[Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>]) -> Option<i64> {
+		if Self::epoch_config(topic).weighted_mode {
+			let weighted: Vec<(i64, T::TokenBalance)> = sorted.iter().map(|message| (message.value, message.deposit)).collect();
+			Self::resolve_weighted(&weighted)
+		} else {
+			let values: Vec<i64> = sorted.iter().map(|message| message.value).collect();
+			Self::resolve(&values)
+		}
+	}
+
+	// distance from the median and reward multiplier in basis points of every message in resolution order,
+	// no multiplier for the penalized ones
+	// values within the tolerance of the median get the top multiplier wherever they are sorted,
+	// as long as the epoch has a reward band
+	fn reward_multipliers(
+		topic: TopicId,
+		sorted: &[Message<T::AccountId, T::Hash, T::TokenBalance, T::BlockNumber>],
+		median: i64,
+		fixed_median: i128,
+	) -> result::Result<(Vec<u64>, Vec<Option<u32>>), &'static str> {
+		let len = sorted.len();
+		// get the indices between the 25th and 75th percentiles
+		let (band_start, band_end) = Self::reward_band(topic, len)?;
+
+		// on the fixed point values for fixed point feeds
+		let distances: Vec<u64> = sorted.iter()
+			.map(|message| Self::distance(message.value, median).saturating_add(Self::fixed_distance(message.fixed, fixed_median)))
+			.collect();
+
+		let epoch_config = Self::epoch_config(topic);
+		let tiers = epoch_config.reward_tiers;
+		let tolerance = epoch_config.median_tolerance;
+		let top_multiplier = tiers.iter().map(|tier| tier.multiplier_bps).max().unwrap_or(10_000);
+		let multipliers = (0..len)
+			.map(|i| if band_start < band_end && distances[i] <= tolerance {
+				Some(top_multiplier)
+			} else {
+				Self::reward_multiplier(&tiers, i, len, band_start, band_end)
+			})
+			.collect();
+
+		Ok((distances, multipliers))
+	}
+
+	// value the epoch would resolve to if `value` was revealed with `deposit` on top of the current reveals,
+	// the current value if nothing would be resolved
+	pub fn simulate_with(value: i64, deposit: T::TokenBalance) -> i64 {
+		let mut messages = Self::valid_messages(DEFAULT_TOPIC);
+		messages.push(Message { value, deposit, ..Default::default() });
+		let (sorted, _) = Self::resolution_order(messages);
+
+		Self::resolve_messages(DEFAULT_TOPIC, &sorted)
+			.map_or_else(|| Self::value(DEFAULT_TOPIC), |median| Self::smooth(DEFAULT_TOPIC, median))
+	}
+
+	// smallest deposit a reveal of `target` needs for the deposit weighted median of the current
+	// reveals to resolve to it, the minimal deposit outside of WeightedMode where the stake does not count
+	pub fn required_stake_for(target: i64) -> T::TokenBalance {
+		let min_deposit = Self::min_deposit();
+		if !Self::epoch_config(DEFAULT_TOPIC).weighted_mode {
+			return min_deposit;
+		}
+
+		// stake revealed below, at and above the target
+		let ignore_zero_values = Self::ignore_zero_values();
+		let (mut below, mut at, mut above) = (T::TokenBalance::zero(), T::TokenBalance::zero(), T::TokenBalance::zero());
+		for message in Self::valid_messages(DEFAULT_TOPIC).iter().filter(|message| !ignore_zero_values || message.value != 0) {
+			let side = if message.value < target { &mut below } else if message.value > target { &mut above } else { &mut at };
+			*side = side.saturating_add(message.deposit);
+		}
+
+		// the stake at the target has to outweigh either side against the other
+		let need = below.saturating_sub(at.saturating_add(above))
+			.max(above.saturating_sub(at.saturating_add(below)))
+			.saturating_add(<T::TokenBalance as As<u64>>::sa(1));
+
+		need.max(min_deposit)
+	}
+
+	// lowest and highest rewarded value if the epoch resolved over the current reveals
+	// `(0, 0)` while nothing would be rewarded
+	pub fn projected_reward_band() -> (i64, i64) {
+		let (sorted, _) = Self::resolution_order(Self::valid_messages(DEFAULT_TOPIC));
+		let median = match Self::resolve_messages(DEFAULT_TOPIC, &sorted) {
+			Some(median) => median,
+			None => return (0, 0),
+		};
+		let fixed_values: Vec<i128> = sorted.iter().map(|message| message.fixed).collect();
+		let fixed_median = Self::resolve_fixed(&fixed_values).unwrap_or_default();
+		let multipliers = match Self::reward_multipliers(DEFAULT_TOPIC, &sorted, median, fixed_median) {
+			Ok((_, multipliers)) => multipliers,
+			Err(_) => return (0, 0),
+		};
+		let mut rewarded = sorted.iter()
+			.zip(multipliers.iter())
+			.filter(|(_, multiplier)| multiplier.is_some())
+			.map(|(message, _)| message.value);
+
+		match rewarded.next() {
+			Some(low) => (low, rewarded.last().unwrap_or(low)),
+			None => (0, 0),
+		}
+	}
+
+	// range of the rewarded indices `[start, end)` in a sorted list of `len` values of `topic`
+	fn reward_band(topic: TopicId, len: usize) -> result::Result<(usize, usize), &'static str> {
+		let step = len.checked_mul(3).ok_or("messages_length step overflow")?;
+		let mode = Self::epoch_config(topic).percentile_mode;
+		if mode == PercentileMode::Interpolated {
+			// too few values to tell the median cluster from the outliers
+			if len < 3 {
+				return Ok((0, 0));
+			}
+			// first index at or above 0.25 * (len - 1), one past the last at or below 0.75 * (len - 1)
+			return Ok(((len + 2) / 4, (step - 3) / 4 + 1));
+		}
+		let (lower_border, upper_border) = match mode {
+			PercentileMode::ExclusiveCeil | PercentileMode::InclusiveCeil => (
+				((len + 3) / 4).saturating_sub(1),
+				((step + 3) / 4).saturating_sub(1),
+			),
+			_ => (len / 4, step / 4),
+		};
+
+		Ok(match mode {
+			PercentileMode::InclusiveFloor | PercentileMode::InclusiveCeil => (lower_border, (upper_border + 1).min(len)),
+			_ => (lower_border + 1, upper_border),
+		})
+	}
+
+	// share of the committers that revealed, scaled down by the interquartile range relative to the median
+	// `revealed / committed * |median| / (|median| + iqr)`, full confidence for a zero median and spread
+	fn confidence(revealed: usize, committed: usize, median: i64, iqr: u64) -> Perbill {
+		if committed == 0 {
+			return Perbill::zero();
+		}
+
+		let median = Self::distance(median, 0);
+		let spread = (median as u128).saturating_add(iqr as u128);
+		let (numerator, denominator) = if spread == 0 {
+			(revealed as u128, committed as u128)
+		} else {
+			((revealed as u128).saturating_mul(median as u128), (committed as u128).saturating_mul(spread))
+		};
+		let billionths = numerator.saturating_mul(1_000_000_000) / denominator;
+
+		Perbill::from_billionths(billionths.min(1_000_000_000) as u32)
+	}
+
+	// multiplier in basis points of the reward of the message at index `i` of `len` sorted messages
+	// with reward tiers, the first tier whose central share of the sorted list contains the message,
+	// without tiers the full reward inside the band
+	fn reward_multiplier(tiers: &[RewardTier], i: usize, len: usize, band_start: usize, band_end: usize) -> Option<u32> {
+		if tiers.is_empty() {
+			return if i >= band_start && i < band_end { Some(10_000) } else { None };
+		}
+
+		// distance of the middle of the message from the middle of the list, in half ranks
+		let doubled = 2 * i as u64 + 1;
+		let offset = if doubled > len as u64 { doubled - len as u64 } else { len as u64 - doubled };
+
+		tiers.iter()
+			.find(|tier| offset.saturating_mul(10_000) < (tier.edge_bps as u64).saturating_mul(len as u64))
+			.map(|tier| tier.multiplier_bps)
+	}
+
+	// sum of the rewards of an epoch
+	fn total_reward(rewards: &[Option<T::TokenBalance>]) -> T::TokenBalance {
+		rewards.iter().fold(T::TokenBalance::zero(), |total, reward| total.saturating_add(reward.unwrap_or_default()))
+	}
+
+	// `amount` rounded down to a multiple of DepositDustThreshold
+	fn without_dust(amount: T::TokenBalance) -> T::TokenBalance {
+		let threshold = Self::deposit_dust_threshold();
+		if threshold.is_zero() {
+			return amount;
+		}
+
+		amount - amount % threshold
+	}
+
+	// scale the rewards of an epoch summing up to `total` to `limit`, rounding each one down
+	fn scale_rewards(rewards: &mut [Option<T::TokenBalance>], total: T::TokenBalance, limit: T::TokenBalance) {
+		for reward in rewards.iter_mut() {
+			*reward = reward.map(|reward| match reward.checked_mul(&limit) {
+				Some(step) => step / total,
+				None if limit < total => reward / (total / limit),
+				None => (reward / total).checked_mul(&limit).unwrap_or(reward),
+			});
+		}
+	}
+
+	// absolute difference between two submitted values, always within the range of u64
+	fn distance(a: i64, b: i64) -> u64 {
+		(a as i128 - b as i128).abs() as u64
+	}
+
+	// absolute difference between two fixed point values, saturated to the range of the plain values
+	fn fixed_distance(a: i128, b: i128) -> u64 {
+		let difference = if a > b { a.saturating_sub(b) } else { b.saturating_sub(a) };
+		if difference > u64::max_value() as i128 { u64::max_value() } else { difference as u64 }
+	}
+
+	// distance from the median weighted rewards are scaled by, none within the tolerance
+	fn reward_distance(distance: u64, tolerance: u64) -> u64 {
+		if distance <= tolerance { 0 } else { distance }
+	}
+
+	// `bps` basis points of `amount`, rounded the other way instead of overflowing for huge amounts
+	fn apply_bps(amount: T::TokenBalance, bps: u32) -> T::TokenBalance {
+		let bps = <T::TokenBalance as As<u64>>::sa(bps as u64);
+		let base = <T::TokenBalance as As<u64>>::sa(10_000);
+
+		match amount.checked_mul(&bps) {
+			Some(step) => step / base,
+			None => (amount / base).checked_mul(&bps).unwrap_or(amount),
+		}
+	}
+
+	// reward for a message inside the band
+	// the full reward per winner, or if `WeightedRewards` is enabled, scaled linearly
+	// from the full reward at the median down to half of it at the edge of the band,
+	// computed in the balance type, dividing first for rewards too large to scale up
+	// and going through basis points of the maximal distance for distances too large to scale by
+	fn reward_for(full: T::TokenBalance, distance: u64, max_distance: u64, weighted: bool) -> T::TokenBalance {
+		if !weighted || max_distance == 0 {
+			return full;
+		}
+		let distance = distance.min(max_distance);
+		let share_bps = ((distance as u128) * 10_000 / (max_distance as u128)) as u32;
+		let distance = <T::TokenBalance as As<u64>>::sa(distance);
+		let max_distance = <T::TokenBalance as As<u64>>::sa(max_distance);
+		let two = <T::TokenBalance as As<u64>>::sa(2);
+
+		// at most half of the full reward is cut as the distance never exceeds the maximal one
+		let cut = match full.checked_mul(&distance) {
+			Some(step) => step / max_distance / two,
+			None if full >= max_distance => (full / max_distance).checked_mul(&distance).unwrap_or(full) / two,
+			None => Self::apply_bps(full, share_bps) / two,
+		};
+
+		full.saturating_sub(cut)
+	}
+}
+
+decl_event!(
+	pub enum Event<T> where AccountId = <T as system::Trait>::AccountId,
+							Balance = <T as token::Trait>::TokenBalance,
+							BlockNumber = <T as system::Trait>::BlockNumber,
+							Hash = <T as system::Trait>::Hash,
+	{
+
+		NewEpochStarted(BlockNumber),
+		HashSubmitted(AccountId, Balance),
+		LockFailed(AccountId, Balance),
+		ValueSubmissionAccepted(AccountId, i64),
+		EvidenceAttached(AccountId, Hash),
+		DepositWithdrawn(AccountId, Balance),
+		RewardClaimed(AccountId, Balance),
+		RewardExpired(AccountId, Balance),
+		EarningsWithdrawn(AccountId, Balance),
+		// account, total amount returned
+		AccountExited(AccountId, Balance),
+		TopicCreated(TopicId),
+		// topic other than the default one and the start of its new epoch
+		TopicEpochStarted(TopicId, BlockNumber),
+		// topic other than the default one, start of the resolved epoch and its value
+		TopicValueSet(TopicId, BlockNumber, i64),
+		StaleCommitmentRefunded(AccountId, Balance),
+		// start of the resolved epoch and its value
+		NewValueSet(BlockNumber, i64),
+		NewFixedValueSet(BlockNumber, i128),
+		// account and amount of a settled deposit, reward or penalty
+		// with the evidence attached to the value, zero if none
+		DepositReturned(AccountId, Balance),
+		RewardPaid(AccountId, Balance, Hash),
+		// winner, reward asset and the amount of it paid
+		AssetRewardPaid(AccountId, token::AssetId, Balance),
+		PenaltyApplied(AccountId, Balance, Hash),
+		// rewards of the epoch and the balance of token_base they were scaled down to
+		InsufficientRewardFunds(Balance, Balance),
+		// account and amount of a transfer from token_base left unpaid
+		PayoutSkipped(AccountId, Balance),
+		// topic of a settlement given up before anything was settled, the amount it needed and the amount available
+		SettlementAborted(TopicId, Balance, Balance),
+		// account that did not reveal its commitment and the forfeited part of its deposit
+		NonRevealPenalized(AccountId, Balance),
+		NonRevealSlashed(AccountId, Balance),
+		// owner of the commitment and the approvals of its delegates its reveal needs
+		RevealDelegatesSet(AccountId, u32),
+		// delegate, owner of the commitment, approvals given so far
+		RevealApproved(AccountId, AccountId, u32),
+		// account and new total deposit of its commitment
+		DepositIncreased(AccountId, Balance),
+		HashUpdated(AccountId, Hash, Balance),
+		// new minimal deposit of the commitments
+		MinDepositChanged(Balance),
+		// new account the rewards are paid from
+		TokenBaseChanged(AccountId),
+		// topic and new lengths of its hash and value submission rounds
+		RoundDurationsChanged(TopicId, BlockNumber, BlockNumber),
+		RevealWindowExtended(BlockNumber, BlockNumber),
+		// block in which the oracle was halted
+		EmergencyHalted(BlockNumber),
+		// block in which the epoch ended without any reveal
+		EpochEndedWithNoValue(BlockNumber),
+		// block in which the automatic settlement failed
+		AutoResolutionFailed(BlockNumber),
+		// resolver, resolved value, resolver reward
+		ResolutionTriggered(AccountId, i64, Balance),
+		// epoch start, total revealed stake
+		EpochInvalidated(BlockNumber, Balance),
+		ValueCarriedForward(i64),
+		// funder, stake revealed by its accounts
+		FunderDominance(AccountId, Balance),
+		// number of distinct funders of an epoch invalidated for having too few
+		TooFewFunders(u32),
+		// epoch start, block of the last reveal
+		EpochStalling(BlockNumber, BlockNumber),
+		// topic, old value, new value, reason hash
+		ValueOverridden(TopicId, i64, i64, Hash),
+
+	}
+);
+
+/// tests for this module
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use primitives::{Blake2Hasher, H256};
+	use runtime_io::with_externalities;
+	use runtime_primitives::{
+		testing::{Digest, DigestItem, Header, UintAuthorityId},
+		traits::{BlakeTwo256, IdentityLookup, Lazy, OnFinalize},
+		BuildStorage,
+  	};
+  	use support::{assert_noop, assert_ok, impl_outer_origin, impl_outer_event};
+
+  	impl_outer_origin! {
+    	pub enum Origin for Test {}
+  	}
+
+	mod schelling {
+		pub use crate::schelling::Event;
+	}
+
+	impl_outer_event! {
+		pub enum TestEvent for Test {
+			schelling<T>,
+			token<T>,
+		}
+	}
+
+	#[derive(Clone, Eq, PartialEq)]
+	pub struct Test;
+	impl system::Trait for Test {
+	  	type Origin = Origin;
 	    type Index = u64;
 	    type BlockNumber = u64;
 	    type Hash = H256;
@@ -277,36 +3435,3383 @@ mod tests {
 	    type AccountId = u64;
 	    type Lookup = IdentityLookup<u64>;
 	    type Header = Header;
-	    type Event = ();
+	    type Event = TestEvent;
+	    type Log = DigestItem;
+	}
+
+	impl consensus::Trait for Test {
 	    type Log = DigestItem;
+	    type SessionKey = UintAuthorityId;
+	    type InherentOfflineReport = ();
+	}
+
+	impl token::Trait for Test {
+	    type Event = TestEvent;
+	    type TokenBalance = u64;
+	}
+	  
+	impl Trait for Test {
+	    type Event = TestEvent;
+	    type Signature = TestSignature;
+	    type OnNewValue = RecordConsumers;
+	}
+
+	thread_local! {
+		static NOTIFIED: std::cell::RefCell<Vec<(u64, i64)>> = std::cell::RefCell::new(Vec::new());
+		static NOTIFIED_FIXED: std::cell::RefCell<Vec<(u64, i128)>> = std::cell::RefCell::new(Vec::new());
+	}
+
+	// records every (consumer, value) notification
+	pub struct RecordConsumers;
+	impl OnNewValue<u64> for RecordConsumers {
+		fn on_new_value(consumer: &u64, value: i64) {
+			NOTIFIED.with(|notified| notified.borrow_mut().push((*consumer, value)));
+		}
+
+		fn on_new_fixed_value(consumer: &u64, value: i128) {
+			NOTIFIED_FIXED.with(|notified| notified.borrow_mut().push((*consumer, value)));
+		}
+	}
+
+	// valid if it names the signer and carries the signed payload
+	#[derive(Clone, PartialEq, Eq, Encode, Decode, Debug)]
+	pub struct TestSignature(u64, Vec<u8>);
+
+	impl Verify for TestSignature {
+		type Signer = u64;
+		fn verify<L: Lazy<[u8]>>(&self, mut msg: L, signer: &u64) -> bool {
+			self.0 == *signer && msg.get() == &self.1[..]
+		}
+	}
+
+	type Schelling = Module<Test>;
+	type Token = token::Module<Test>;
+	type System = system::Module<Test>;
+
+	// account holding the token supply and paying out rewards
+	const BASE: u64 = 100;
+	const DEPOSIT: u64 = 10;
+
+	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
+		new_test_ext_with(BASE, DEPOSIT, 0)
+	}
+
+	fn new_test_ext_with(token_base: u64, min_deposit: u64, epoch_start: u64) -> runtime_io::TestExternalities<Blake2Hasher> {
+		let mut t = system::GenesisConfig::<Test>::default().build_storage().unwrap().0;
+		t.extend(token::GenesisConfig::<Test>{
+			total_supply: 1_000_000,
+		}.build_storage().unwrap().0);
+		t.extend(GenesisConfig::<Test>{
+			token_base,
+			min_deposit,
+			epoch_start,
+		}.build_storage().unwrap().0);
+		t.into()
+	}
+
+	fn events() -> Vec<TestEvent> {
+		System::events().into_iter().map(|record| record.event).collect()
+	}
+
+	fn root() -> Origin {
+		system::RawOrigin::Root.into()
+	}
+
+	// fund every participant from the token base, allow the base to pay them rewards
+	// and start the first epoch on block 1
+	fn setup(accounts: &[u64]) {
+		assert_ok!(Token::init(BASE));
+
+		for &who in accounts {
+			assert_ok!(Token::transfer(Origin::signed(BASE), who, 1000));
+			assert_ok!(Token::approve(Origin::signed(BASE), who, 1000));
+		}
+
+		System::set_block_number(1);
+		assert_ok!(Schelling::new_epoch(root(), DEFAULT_TOPIC));
+	}
+
+	// salt of the test commitments of an account
+	fn salt(who: u64) -> H256 {
+		H256::from([who as u8; 32])
+	}
+
+	// the hash a reveal of `value` by `who` is checked against
+	fn commitment<V: Encode>(who: u64, value: V) -> H256 {
+		(who, value, salt(who)).using_encoded(BlakeTwo256::hash)
+	}
+
+	fn commit(who: u64, value: i64) {
+		commit_with(who, value, DEPOSIT);
+	}
+
+	fn commit_with(who: u64, value: i64, deposit: u64) {
+		assert_ok!(Schelling::submit_hash(Origin::signed(who), DEFAULT_TOPIC, commitment(who, value), deposit));
+	}
+
+	fn reveal(who: u64, value: i64) -> Result {
+		Schelling::submit_value(Origin::signed(who), DEFAULT_TOPIC, value, salt(who))
+	}
+
+	fn go_to_reveal() {
+		System::set_block_number(Schelling::epoch_start(DEFAULT_TOPIC) + 60);
+	}
+
+	fn go_to_epoch_end() {
+		System::set_block_number(Schelling::epoch_start(DEFAULT_TOPIC) + 101);
+	}
+
+	// commit, reveal and settle a full epoch with the given (account, value) pairs
+	fn run_epoch(submissions: &[(u64, i64)]) {
+		for &(who, value) in submissions {
+			commit(who, value);
+		}
+		go_to_reveal();
+		for &(who, value) in submissions {
+			assert_ok!(reveal(who, value));
+		}
+		go_to_epoch_end();
+		assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+	}
+
+	#[test]
+	fn dummy_test() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(1, 1);
+		});
+	}
+
+	#[test]
+	fn weighted_rewards_favour_the_median() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+			setup(&accounts);
+			assert_ok!(Schelling::set_weighted_rewards(root(), true));
+
+			// median is account 5 (value 50), the band is accounts 3 to 7
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60), (7, 70), (8, 80), (9, 90)]);
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 50);
+			assert_eq!(Token::balance_of(5), 1100);
+			assert_eq!(Token::balance_of(4), 1075);
+			assert_eq!(Token::balance_of(6), 1075);
+			assert_eq!(Token::balance_of(3), 1050);
+			assert_eq!(Token::balance_of(7), 1050);
+			assert!(Token::balance_of(5) > Token::balance_of(4));
+		});
+	}
+
+	#[test]
+	fn weighted_rewards_keep_the_full_range_of_the_balance() {
+		with_externalities(&mut new_test_ext(), || {
+			let full = u64::max_value();
+			assert_eq!(Schelling::reward_for(full, 0, 4, true), full);
+			// the reward times the distance overflows, the reward is divided first
+			assert_eq!(Schelling::reward_for(full, 2, 4, true), full - full / 4);
+			assert_eq!(Schelling::reward_for(full, 4, 4, true), full - full / 4 * 2);
+			assert_eq!(Schelling::reward_for(100, 4, 4, true), 50);
+			// distances wider than the reward can be scaled by
+			assert_eq!(Schelling::reward_for(100, u64::max_value(), u64::max_value(), true), 50);
+			assert_eq!(Schelling::reward_for(100, 1 << 61, 1 << 62, true), 75);
+		});
+	}
+
+	#[test]
+	fn weighted_rewards_can_be_set_by_root_only() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(Schelling::set_weighted_rewards(Origin::signed(1), true), "bad origin: expected to be a root origin");
+			assert_ok!(Schelling::set_weighted_rewards(root(), true));
+			assert!(Schelling::weighted_rewards());
+		});
+	}
+
+	#[test]
+	fn deposit_stats_cover_committed_and_revealed_messages() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_eq!(Schelling::deposit_stats(), DepositStats::default());
+
+			commit_with(1, 5, 10);
+			commit_with(2, 0, 20);
+			commit_with(3, 0, 60);
+			go_to_reveal();
+			assert_ok!(reveal(1, 5));
+
+			assert_eq!(Schelling::deposit_stats(), DepositStats {
+				count: 3,
+				total: 90,
+				min: 10,
+				max: 60,
+				average: 30,
+			});
+			assert_eq!(Schelling::committers(DEFAULT_TOPIC), vec![2, 3]);
+		});
+	}
+
+	#[test]
+	fn auto_rollover_keeps_deposit_for_next_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_auto_rollover(Origin::signed(2), true));
+
+			// account 2 holds the median and is rewarded, its deposit stays locked
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Schelling::standing_deposit(2), DEPOSIT);
+			assert_eq!(Token::balance_of(2), 1000 - DEPOSIT + 100);
+			assert_eq!(Schelling::standing_deposit(1), 0);
+
+			// the next commit is covered by the standing deposit without touching the balance
+			commit(2, 20);
+			assert_eq!(Schelling::standing_deposit(2), 0);
+			assert_eq!(Token::balance_of(2), 1000 - DEPOSIT + 100);
+			assert_eq!(Token::locked_deposits(commitment(2, 20i64)), DEPOSIT);
+		});
+	}
+
+	#[test]
+	fn raising_min_deposit_keeps_existing_commitments() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			for who in 1..=3 {
+				commit(who, who as i64 * 10);
+			}
+
+			// the minimum goes above the deposits already locked
+			<MinDeposit<Test>>::put(DEPOSIT * 5);
+			assert_noop!(
+				Schelling::submit_hash(Origin::signed(4), DEFAULT_TOPIC, commitment(4, 40i64), DEPOSIT),
+				"The deposit is not high enough"
+			);
+
+			go_to_reveal();
+			for who in 1..=3 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+			assert_eq!(Token::balance_of(2), 1000 + 100);
+		});
+	}
+
+	#[test]
+	fn simulate_with_matches_actual_submission() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			for &(who, value) in [(1, 10), (2, 50), (3, 30)].iter() {
+				commit(who, value);
+			}
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 50));
+
+			assert_eq!(Schelling::simulate_with(100, DEPOSIT), 50);
+			assert_eq!(Schelling::simulate_with(30, DEPOSIT), 30);
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 2);
+
+			assert_ok!(reveal(3, 30));
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 30);
+		});
+	}
+
+	#[test]
+	fn projections_follow_the_resolution_rules() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_weighted_mode(root(), true));
+			assert_ok!(Schelling::set_ignore_zero_values(root(), true));
+			assert_ok!(Schelling::set_median_tolerance(root(), 10));
+			assert_ok!(Schelling::set_smoothing_bps(root(), 5_000));
+
+			for &(who, value, deposit) in [(1, 10, DEPOSIT), (2, 20, DEPOSIT), (3, 30, DEPOSIT * 5)].iter() {
+				commit_with(who, value, deposit);
+			}
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 20));
+
+			// 50 of the 70 deposited would be on 30, a zero is no data and leaves 10 and 20 tied
+			assert_eq!(Schelling::simulate_with(30, DEPOSIT * 5), 30);
+			assert_eq!(Schelling::simulate_with(0, DEPOSIT * 5), 15);
+
+			// 20 and 30 are within the tolerance of the weighted median
+			assert_ok!(reveal(3, 30));
+			assert_eq!(Schelling::projected_reward_band(), (20, 30));
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 30);
+			let rewarded: Vec<u64> = [1, 2, 3].iter().cloned().filter(|who| Token::balance_of(who) > 1000).collect();
+			assert_eq!(rewarded, vec![2, 3]);
+
+			// half of the jump to 50 goes through once there is a previous value
+			commit(1, 50);
+			go_to_reveal();
+			assert_eq!(Schelling::simulate_with(50, DEPOSIT), 40);
+			assert_ok!(reveal(1, 50));
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 40);
+		});
+	}
+
+	#[test]
+	fn oversized_reveal_is_rejected() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			commit(1, 10);
+			go_to_reveal();
+
+			// an encoded u64 takes 8 bytes
+			assert_ok!(Schelling::set_max_reveal_encoded_len(root(), 4));
+			assert_noop!(reveal(1, 10), "The revealed value is too large");
+
+			assert_ok!(Schelling::set_max_reveal_encoded_len(root(), 8));
+			assert_ok!(reveal(1, 10));
+		});
+	}
+
+	#[test]
+	fn feed_width_fits_the_reveal_size_limit() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Schelling::set_feed_width(root(), 3));
+			// four values and the length prefix take 33 bytes
+			assert_noop!(Schelling::set_feed_width(root(), 4), "The reveal size limit is too small for the feed width");
+			assert_noop!(Schelling::set_max_reveal_encoded_len(root(), 24), "The reveal size limit is too small for the feed width");
+
+			assert_ok!(Schelling::set_max_reveal_encoded_len(root(), 33));
+			assert_ok!(Schelling::set_feed_width(root(), 4));
+		});
+	}
+
+	#[test]
+	fn recycled_penalties_fund_next_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_penalty_recycle_bps(root(), 5_000));
+
+			// accounts 1 and 3 are out of the band and lose 5 tokens each
+			for who in 1..=3 {
+				commit_with(who, who as i64 * 10, 500);
+			}
+			go_to_reveal();
+			for who in 1..=3 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::reward_pool(DEFAULT_TOPIC), 5);
+			assert_eq!(Token::balance_of(2), 1100);
+
+			// the only winner of the next epoch takes the whole pool
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Token::balance_of(2), 1100 + 105);
+			assert_eq!(Schelling::reward_pool(DEFAULT_TOPIC), 1);
+		});
+	}
+
+	#[test]
+	fn penalty_recycle_bps_is_bounded() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(
+				Schelling::set_penalty_recycle_bps(root(), 10_001),
+				"The recycled share can not exceed 10000 basis points"
+			);
+		});
+	}
+
+	#[test]
+	fn last_resolution_decodes_to_summary() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+
+			let encoded = Schelling::last_resolution_encoded();
+			let resolution = Resolution::<u64, u64>::decode(&mut &encoded[..]).unwrap();
+			assert_eq!(resolution, Resolution {
+				value: 20,
+				epoch_start: 1,
+				revealed: 3,
+				winners: 1,
+				deposits: DepositStats {
+					count: 3,
+					total: 30,
+					min: 10,
+					max: 10,
+					average: 10,
+				},
+			});
+		});
+	}
+
+	#[test]
+	fn override_value_sets_value_and_logs_it() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+
+			let reason = H256::from([7u8; 32]);
+			assert_noop!(
+				Schelling::override_value(Origin::signed(1), DEFAULT_TOPIC, 25, reason),
+				"bad origin: expected to be a root origin"
+			);
+
+			System::set_block_number(150);
+			assert_ok!(Schelling::override_value(root(), DEFAULT_TOPIC, 25, reason));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+			assert_eq!(Schelling::value_overrides(), vec![ValueOverride {
+				topic: DEFAULT_TOPIC,
+				block_number: 150,
+				old_value: 20,
+				new_value: 25,
+				reason_hash: reason,
+			}]);
+		});
+	}
+
+	#[test]
+	fn composite_feed_resolves_per_position() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_feed_width(root(), 3));
+			let reveals = [(1, vec![10i64, 200, 3000]), (2, vec![30, 100, 1000]), (3, vec![20, 300, 2000])];
+			for (who, values) in reveals.iter() {
+				assert_ok!(Schelling::submit_hash(Origin::signed(*who), DEFAULT_TOPIC, commitment(*who, values.clone()), DEPOSIT));
+			}
+			go_to_reveal();
+
+			assert_noop!(
+				reveal(1, 10),
+				"The feed expects composite values, use submit_values"
+			);
+			assert_noop!(
+				Schelling::submit_values(Origin::signed(1), DEFAULT_TOPIC, vec![10, 200], salt(1)),
+				"The number of values does not match the feed width"
+			);
+			for (who, values) in reveals.iter() {
+				assert_ok!(Schelling::submit_values(Origin::signed(*who), DEFAULT_TOPIC, values.clone(), salt(*who)));
+			}
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::composite_value(DEFAULT_TOPIC), vec![20, 200, 2000]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+			// account 3 holds the median of the first position
+			assert_eq!(Token::balance_of(3), 1100);
+		});
+	}
+
+	#[test]
+	fn new_epoch_is_rejected_while_epoch_is_active() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			commit(1, 10);
+
+			System::set_block_number(10);
+			assert_noop!(
+				Schelling::new_epoch(root(), DEFAULT_TOPIC),
+				"The current epoch is not resolved yet, use force_new_epoch"
+			);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 1);
+
+			assert_ok!(Schelling::force_new_epoch(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 10);
+		});
+	}
+
+	#[test]
+	fn forced_epoch_returns_the_pending_reveals() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			for who in 1..4 {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 20));
+
+			assert_ok!(Schelling::force_new_epoch(root(), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(1), 999);
+			assert_eq!(Token::balance_of(2), 1100);
+			assert!(Schelling::valid_messages(DEFAULT_TOPIC).is_empty());
+			assert_eq!(Schelling::distinct_value_count(DEFAULT_TOPIC), 0);
+			assert!(!<RevealedSet<Test>>::exists(&DEFAULT_TOPIC, &(1, 102)));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+			assert!(Schelling::value_carried());
+
+			// the commitment left unrevealed is withdrawn in the new epoch
+			assert_ok!(Schelling::withdraw(Origin::signed(3), DEFAULT_TOPIC));
+			run_epoch(&[(1, 10), (2, 20), (3, 10)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 10);
+		});
+	}
+
+	#[test]
+	fn resolution_starts_the_next_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 102);
+			assert!(Schelling::epoch_active(DEFAULT_TOPIC));
+		});
+	}
+
+	#[test]
+	fn lifetime_penalties_accumulate_over_epochs() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+
+			// accounts 1 and 3 lose 5 tokens each
+			for who in 1..=3 {
+				commit_with(who, who as i64 * 10, 500);
+			}
+			go_to_reveal();
+			for who in 1..=3 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::lifetime_penalties(), 10);
+
+			// accounts 1 and 3 lose 1 token each
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Schelling::lifetime_penalties(), 12);
+		});
+	}
+
+	#[test]
+	fn percentile_modes_pin_winner_sets() {
+		with_externalities(&mut new_test_ext(), || {
+			// 8 values: 25th and 75th percentiles at indices 2 and 6 (floor), 1 and 5 (nearest rank),
+			// 1.75 and 5.25 (interpolated)
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 8), Ok((2, 6)));
+
+			assert_ok!(Schelling::set_percentile_mode(root(), PercentileMode::ExclusiveFloor));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 8), Ok((3, 6)));
+
+			assert_ok!(Schelling::set_percentile_mode(root(), PercentileMode::ExclusiveCeil));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 8), Ok((2, 5)));
+
+			assert_ok!(Schelling::set_percentile_mode(root(), PercentileMode::InclusiveFloor));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 8), Ok((2, 7)));
+
+			assert_ok!(Schelling::set_percentile_mode(root(), PercentileMode::InclusiveCeil));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 8), Ok((1, 6)));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 1), Ok((0, 1)));
+		});
+	}
+
+	#[test]
+	fn inclusive_mode_rewards_the_percentiles() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			assert_ok!(Schelling::set_percentile_mode(root(), PercentileMode::InclusiveCeil));
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60), (7, 70), (8, 80)]);
+
+			// indices 1 to 5 are rewarded, the others pay a penalty of 1
+			let balances: Vec<u64> = accounts.iter().map(|who| Token::balance_of(who)).collect();
+			assert_eq!(balances, vec![999, 1100, 1100, 1100, 1100, 1100, 999, 999]);
+		});
+	}
+
+	#[test]
+	fn failed_lock_emits_event() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+
+			// account 9 was never funded
+			assert_eq!(
+				Schelling::submit_hash(Origin::signed(9), DEFAULT_TOPIC, commitment(9, 10i64), DEPOSIT),
+				Err("Account does not own this token")
+			);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::LockFailed(9, DEPOSIT))));
+			assert!(!<Messages<Test>>::exists(&DEFAULT_TOPIC, &9));
+		});
+	}
+
+	#[test]
+	fn stake_signal_mode_resolves_to_concentrated_stake() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_stake_signal_mode(root(), true));
+
+			let settle = |deposits: [u64; 3]| {
+				for (who, deposit) in (1..=3).zip(deposits.iter()) {
+					commit_with(who, (*deposit / DEPOSIT) as i64, *deposit);
+				}
+				go_to_reveal();
+				assert_noop!(reveal(1, 5), "The deposit is the signal, use confirm_stake");
+				for who in 1..=3 {
+					assert_ok!(Schelling::confirm_stake(Origin::signed(who), DEFAULT_TOPIC, salt(who)));
+				}
+				go_to_epoch_end();
+				assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			};
+
+			// stake concentrates around 2 minimal deposits
+			settle([DEPOSIT, DEPOSIT * 2, DEPOSIT * 2]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 2);
+
+			// stake concentrates around 1 minimal deposit
+			settle([DEPOSIT, DEPOSIT, DEPOSIT * 3]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 1);
+		});
+	}
+
+	#[test]
+	fn stake_signal_saturates_at_the_largest_value() {
+		with_externalities(&mut new_test_ext_with(BASE, 1, 0), || {
+			assert_eq!(Schelling::stake_signal(u64::max_value()), i64::max_value());
+			assert_eq!(Schelling::stake_signal(i64::max_value() as u64), i64::max_value());
+			assert_eq!(Schelling::stake_signal(i64::max_value() as u64 - 1), i64::max_value() - 1);
+
+			<MinDeposit<Test>>::put(DEPOSIT);
+			assert_eq!(Schelling::stake_signal(u64::max_value()), (u64::max_value() / DEPOSIT) as i64);
+		});
+	}
+
+	#[test]
+	fn reveal_of_previous_epoch_commitment_is_refunded() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2]);
+			commit(1, 10);
+			assert_eq!(Token::balance_of(1), 1000 - DEPOSIT);
+
+			System::set_block_number(10);
+			assert_ok!(Schelling::force_new_epoch(root(), DEFAULT_TOPIC));
+			commit(2, 20);
+
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 20));
+
+			assert_eq!(Token::balance_of(1), 1000);
+			assert!(!<Messages<Test>>::exists(&DEFAULT_TOPIC, &1));
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 1);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::StaleCommitmentRefunded(1, DEPOSIT))));
+		});
+	}
+
+	#[test]
+	fn long_gap_between_reveals_flags_stalling() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2]);
+			assert_ok!(Schelling::set_reveal_gap_limit(root(), 10));
+			commit(1, 10);
+			commit(2, 20);
+
+			System::set_block_number(52);
+			assert_ok!(reveal(1, 10));
+
+			<Schelling as OnFinalize<u64>>::on_finalize(62);
+			assert!(!Schelling::stalling(DEFAULT_TOPIC));
+
+			<Schelling as OnFinalize<u64>>::on_finalize(63);
+			assert!(Schelling::stalling(DEFAULT_TOPIC));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::EpochStalling(1, 52))));
+
+			// a new reveal clears the flag
+			System::set_block_number(64);
+			assert_ok!(reveal(2, 20));
+			assert!(!Schelling::stalling(DEFAULT_TOPIC));
+		});
+	}
+
+	#[test]
+	fn relayed_commits_are_verified_and_locked() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			let sign = |who: u64, deposit: u64| {
+				let payload = (DEFAULT_TOPIC, who, commitment(who, 10i64), deposit, Schelling::epoch_start(DEFAULT_TOPIC)).encode();
+				(who, commitment(who, 10i64), deposit, TestSignature(who, payload))
+			};
+
+			// account 3 signed a different deposit than the one relayed
+			let (who, hash, _, signature) = sign(3, DEPOSIT);
+			let forged = (who, hash, DEPOSIT * 2, signature);
+			assert_noop!(
+				Schelling::relay_commits(Origin::signed(9), DEFAULT_TOPIC, vec![sign(1, DEPOSIT), sign(2, DEPOSIT), forged]),
+				"Invalid commit signature"
+			);
+
+			assert_ok!(Schelling::relay_commits(Origin::signed(9), DEFAULT_TOPIC, vec![sign(1, DEPOSIT), sign(2, DEPOSIT)]));
+			assert!(<Messages<Test>>::exists(&DEFAULT_TOPIC, &1));
+			assert!(<Messages<Test>>::exists(&DEFAULT_TOPIC, &2));
+			assert!(!<Messages<Test>>::exists(&DEFAULT_TOPIC, &3));
+			assert_eq!(Token::balance_of(1), 1000 - DEPOSIT);
+			assert_eq!(Token::balance_of(2), 1000 - DEPOSIT);
+		});
+	}
+
+	#[test]
+	fn relayed_batch_is_bounded() {
+		with_externalities(&mut new_test_ext(), || {
+			let commits = (0..MAX_RELAYED_COMMITS as u64 + 1)
+				.map(|who| (who, commitment(who, 10i64), DEPOSIT, TestSignature(who, Vec::new())))
+				.collect();
+			assert_noop!(Schelling::relay_commits(Origin::signed(9), DEFAULT_TOPIC, commits), "Too many commits in one batch");
+		});
+	}
+
+	#[test]
+	fn delegates_reveal_once_the_threshold_approved() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			for who in 1..=3 {
+				commit(who, who as i64 * 10);
+			}
+			assert_noop!(
+				Schelling::set_reveal_delegates(Origin::signed(1), DEFAULT_TOPIC, vec![7, 8, 9], 4),
+				"Invalid delegate threshold"
+			);
+			assert_noop!(
+				Schelling::set_reveal_delegates(Origin::signed(1), DEFAULT_TOPIC, vec![7, 7, 9], 2),
+				"The reveal delegates must be distinct"
+			);
+			assert_ok!(Schelling::set_reveal_delegates(Origin::signed(1), DEFAULT_TOPIC, vec![7, 8, 9], 2));
+			assert_noop!(
+				Schelling::approve_reveal(Origin::signed(7), DEFAULT_TOPIC, 1, 10, salt(1)),
+				"Reveals can only be approved during the value submission round"
+			);
+
+			go_to_reveal();
+			assert_noop!(
+				Schelling::approve_reveal(Origin::signed(6), DEFAULT_TOPIC, 1, 10, salt(1)),
+				"The account is not a reveal delegate of the commitment"
+			);
+			assert_noop!(
+				Schelling::approve_reveal(Origin::signed(7), DEFAULT_TOPIC, 1, 11, salt(1)),
+				"The revealed value does not match the committed hash"
+			);
+
+			// one approval of the three delegates is not enough
+			assert_ok!(Schelling::approve_reveal(Origin::signed(7), DEFAULT_TOPIC, 1, 10, salt(1)));
+			assert_noop!(
+				Schelling::approve_reveal(Origin::signed(7), DEFAULT_TOPIC, 1, 10, salt(1)),
+				"The delegate already approved the reveal"
+			);
+			assert!(!Schelling::has_revealed(DEFAULT_TOPIC, 1));
+
+			assert_ok!(Schelling::approve_reveal(Origin::signed(9), DEFAULT_TOPIC, 1, 10, salt(1)));
+			assert!(Schelling::has_revealed(DEFAULT_TOPIC, 1));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::RevealApproved(9, 1, 2))));
+			assert_noop!(
+				Schelling::approve_reveal(Origin::signed(8), DEFAULT_TOPIC, 1, 10, salt(1)),
+				"Message hash was not submitted"
+			);
+
+			assert_ok!(reveal(2, 20));
+			assert_ok!(reveal(3, 30));
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+		});
+	}
+
+	#[test]
+	fn tight_full_participation_is_more_confident() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+
+			// everybody reveals, interquartile range of 1 around a median of 100
+			run_epoch(&[(1, 100), (2, 100), (3, 101), (4, 100)]);
+			let tight = Schelling::last_confidence();
+			assert_eq!(tight, Perbill::from_billionths(990_099_009));
+
+			// half of the committers reveal, interquartile range of 90 around a median of 55
+			for &(who, value) in [(1, 10), (2, 100), (3, 0), (4, 0)].iter() {
+				commit(who, value);
+			}
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 100));
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			let sparse = Schelling::last_confidence();
+			assert_eq!(sparse, Perbill::from_billionths(189_655_172));
+		});
+	}
+
+	#[test]
+	fn equal_values_are_ordered_by_owner() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			let submissions = [(1, 10), (2, 10), (3, 20), (4, 30)];
+			for &(who, value) in submissions.iter() {
+				commit(who, value);
+			}
+			go_to_reveal();
+			for &who in [4, 2, 1, 3].iter() {
+				assert_ok!(reveal(who, submissions[who as usize - 1].1));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// sorted as accounts 1, 2, 3, 4 the middle ones win, the tie at the border goes by owner
+			assert_eq!(Token::balance_of(2), 1100);
+			assert_eq!(Token::balance_of(3), 1100);
+			assert_eq!(Token::balance_of(1), 999);
+			assert_eq!(Token::balance_of(4), 999);
+		});
+	}
+
+	#[test]
+	fn escrowed_rewards_are_claimed_within_the_window() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_reward_escrow(root(), true, 10));
+
+			// resolved on block 102, claimable until block 112
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Token::balance_of(2), 1000);
+			assert_eq!(Schelling::claimable_reward((DEFAULT_TOPIC, 2, 1)), 100);
+			assert_eq!(Schelling::escrowed_rewards(), 100);
+			assert_noop!(Schelling::claim_reward(Origin::signed(1), DEFAULT_TOPIC, 1), "There is no reward to claim for this epoch");
+
+			System::set_block_number(112);
+			assert_ok!(Schelling::claim_reward(Origin::signed(2), DEFAULT_TOPIC, 1));
+			assert_eq!(Token::balance_of(2), 1100);
+			assert_eq!(Schelling::escrowed_rewards(), 0);
+			assert!(!<ClaimableRewards<Test>>::exists((2, 1)));
+		});
+	}
+
+	#[test]
+	fn unclaimed_escrowed_rewards_expire() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_reward_escrow(root(), true, 10));
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+
+			System::set_block_number(113);
+			assert_ok!(Schelling::claim_reward(Origin::signed(2), DEFAULT_TOPIC, 1));
+			assert_eq!(Token::balance_of(2), 1000);
+			assert_eq!(Token::balance_of(BASE), 1_000_000 - 3 * 1000 + 2);
+			assert_eq!(Schelling::escrowed_rewards(), 0);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::RewardExpired(2, 100))));
+		});
+	}
+
+	#[test]
+	fn escrowed_rewards_are_kept_out_of_the_reward_funds() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_reward_escrow(root(), true, 1000));
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Schelling::escrowed_rewards(), 100);
+
+			// token_base holds the escrowed reward and half of the next one
+			let balance = Token::balance_of(BASE);
+			assert_ok!(Token::transfer(Origin::signed(BASE), 99, balance - 150));
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Schelling::claimable_reward((DEFAULT_TOPIC, 2, 102)), 50);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::InsufficientRewardFunds(100, 50))));
+
+			// both rewards can be paid out
+			assert_ok!(Schelling::claim_reward(Origin::signed(2), DEFAULT_TOPIC, 1));
+			assert_ok!(Schelling::claim_reward(Origin::signed(2), DEFAULT_TOPIC, 102));
+			assert_eq!(Token::balance_of(2), 1150);
+			assert_eq!(Schelling::escrowed_rewards(), 0);
+		});
+	}
+
+	#[test]
+	fn feed_config_reports_decimals_and_unit() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Schelling::set_feed_config(root(), 2, b"USD".to_vec()));
+			assert_eq!(Schelling::feed_config(), FeedConfig {
+				decimals: 2,
+				unit: b"USD".to_vec(),
+			});
+
+			assert_noop!(
+				Schelling::set_feed_config(root(), 2, vec![b'X'; MAX_UNIT_LEN + 1]),
+				"The unit label is too long"
+			);
+		});
+	}
+
+	#[test]
+	fn send_rewards_waits_for_the_end_of_the_reveal_round() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			for who in 1..=3 {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 20));
+
+			System::set_block_number(100);
+			assert_noop!(Schelling::send_rewards(root(), DEFAULT_TOPIC), "It's not the time to send out the rewards yet");
+
+			// the forced path settles right away
+			assert_ok!(Schelling::force_send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 15);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 100);
+		});
+	}
+
+	#[test]
+	fn send_rewards_is_allowed_once_the_reveal_round_closed() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			for who in 1..=3 {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			for who in 1..=3 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+
+			System::set_block_number(101);
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+		});
+	}
+
+	#[test]
+	fn reward_tiers_scale_rewards_by_position() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			assert_ok!(Schelling::set_reward_tiers(root(), vec![
+				RewardTier { edge_bps: 2_500, multiplier_bps: 20_000 },
+				RewardTier { edge_bps: 5_000, multiplier_bps: 10_000 },
+				RewardTier { edge_bps: 7_500, multiplier_bps: 5_000 },
+			]));
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60), (7, 70), (8, 80)]);
+
+			// the central 2 get 2x, the next 2 get 1x, the next 2 get half, the outer 2 are penalized
+			let balances: Vec<u64> = accounts.iter().map(|who| Token::balance_of(who)).collect();
+			assert_eq!(balances, vec![999, 1050, 1100, 1200, 1200, 1100, 1050, 999]);
+		});
+	}
+
+	#[test]
+	fn reward_tiers_are_validated() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_noop!(
+				Schelling::set_reward_tiers(root(), vec![
+					RewardTier { edge_bps: 5_000, multiplier_bps: 10_000 },
+					RewardTier { edge_bps: 2_500, multiplier_bps: 20_000 },
+				]),
+				"Tier edges should be strictly increasing"
+			);
+			assert_noop!(
+				Schelling::set_reward_tiers(root(), vec![RewardTier { edge_bps: 10_001, multiplier_bps: 10_000 }]),
+				"Tier edges should be within 10000 basis points"
+			);
+		});
+	}
+
+	#[test]
+	fn duplicate_reveals_do_not_inflate_distinct_count() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			for &(who, value) in [(1, 10), (2, 10), (3, 20), (4, 30)].iter() {
+				commit(who, value);
+			}
+			go_to_reveal();
+
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 10));
+			assert_ok!(reveal(3, 20));
+			assert_eq!(Schelling::distinct_value_count(DEFAULT_TOPIC), 2);
+
+			assert_ok!(reveal(4, 30));
+			assert_eq!(Schelling::distinct_value_count(DEFAULT_TOPIC), 3);
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::distinct_value_count(DEFAULT_TOPIC), 0);
+			assert_eq!(Schelling::revealed_value_count(DEFAULT_TOPIC, 10), 0);
+		});
+	}
+
+	#[test]
+	fn accrued_earnings_are_withdrawn_at_once() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::set_accrue_earnings(root(), true));
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+
+			// the rewards stay with token_base until withdrawn
+			assert_eq!(Schelling::accrued_earnings(3), 200);
+			assert_eq!(Token::balance_of(3), 1000);
+
+			assert_ok!(Schelling::withdraw_earnings(Origin::signed(3)));
+			assert_eq!(Token::balance_of(3), 1200);
+			assert_eq!(Schelling::accrued_earnings(3), 0);
+			assert_noop!(Schelling::withdraw_earnings(Origin::signed(3)), "There are no earnings to withdraw");
+		});
+	}
+
+	#[test]
+	fn unconfigured_reward_source_prevents_resolution() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			for who in 1..5 {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			for who in 1..5 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+			<TokenBase<Test>>::kill();
+
+			go_to_epoch_end();
+			assert_noop!(Schelling::send_rewards(root(), DEFAULT_TOPIC), "The reward source token_base is not configured");
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 4);
+		});
+	}
+
+	#[test]
+	fn value_version_increments_once_per_resolution() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_eq!(Schelling::value_version(), 0);
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+			assert_eq!(Schelling::value_version(), 1);
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+			assert_eq!(Schelling::value_version(), 2);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+		});
+	}
+
+	#[test]
+	fn zero_values_are_ignored_when_enabled() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5, 6]);
+			assert_ok!(Schelling::set_ignore_zero_values(root(), true));
+
+			run_epoch(&[(1, 0), (2, 0), (3, 10), (4, 20), (5, 30), (6, 40)]);
+
+			// resolved over 10, 20, 30, 40, the reporters without data are refunded in full
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+			assert_eq!(Token::balance_of(1), 1000);
+			assert_eq!(Token::balance_of(2), 1000);
+			assert_eq!(Token::balance_of(5), 1100);
+			assert_eq!(Token::balance_of(3), 999);
+		});
+	}
+
+	#[test]
+	fn blocks_until_resolution_counts_down() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_eq!(Schelling::blocks_until_resolution(), 100);
+
+			System::set_block_number(51);
+			assert_eq!(Schelling::blocks_until_resolution(), 50);
+
+			System::set_block_number(100);
+			assert_eq!(Schelling::blocks_until_resolution(), 1);
+
+			System::set_block_number(101);
+			assert_eq!(Schelling::blocks_until_resolution(), 0);
+
+			System::set_block_number(150);
+			assert_eq!(Schelling::blocks_until_resolution(), 0);
+		});
+	}
+
+	#[test]
+	fn low_total_stake_invalidates_the_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::set_min_total_stake(root(), 50));
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+
+			// 40 revealed against 50 required, no value and full refunds
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 0);
+			for who in 1..5 {
+				assert_eq!(Token::balance_of(who), 1000);
+			}
+			assert!(events().contains(&TestEvent::schelling(RawEvent::EpochInvalidated(1, 40))));
+		});
+	}
+
+	#[test]
+	fn high_total_stake_resolves_normally() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::set_min_total_stake(root(), 40));
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+			assert_eq!(Token::balance_of(3), 1100);
+		});
+	}
+
+	#[test]
+	fn resolver_is_recorded_and_compensated() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			assert_ok!(Schelling::set_resolver_reward(root(), 5));
+			for who in 1..5 {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			for who in 1..5 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+
+			System::set_block_number(100);
+			assert_noop!(Schelling::trigger_resolution(Origin::signed(5), DEFAULT_TOPIC), "It's not the time to send out the rewards yet");
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::trigger_resolution(Origin::signed(5), DEFAULT_TOPIC));
+			assert_eq!(Schelling::last_resolver(), Some(5));
+			assert_eq!(Token::balance_of(5), 1005);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::ResolutionTriggered(5, 25, 5))));
+		});
+	}
+
+	#[test]
+	fn unpayable_resolver_reward_does_not_fail_the_resolution() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::set_resolver_reward(root(), 5));
+			for who in 1..5 {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			for who in 1..5 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+			go_to_epoch_end();
+
+			// account 6 has no allowance from token_base
+			assert_ok!(Schelling::trigger_resolution(Origin::signed(6), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+			assert_eq!(Schelling::last_resolver(), Some(6));
+			assert_eq!(Token::balance_of(6), 0);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::PayoutSkipped(6, 5))));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::ResolutionTriggered(6, 25, 0))));
+		});
+	}
+
+	// commit in the first epoch, let the reveal round pass and withdraw before the epoch is settled
+	fn withdraw_after_missed_reveal(who: u64) {
+		commit(who, 10);
+		go_to_epoch_end();
+		assert_ok!(Schelling::withdraw(Origin::signed(who), DEFAULT_TOPIC));
+	}
+
+	#[test]
+	fn unrevealed_forfeit_goes_to_base() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 5_000, ForfeitDestination::Base));
+			let base_balance = Token::balance_of(BASE);
+
+			withdraw_after_missed_reveal(1);
+
+			assert_eq!(Token::balance_of(1), 995);
+			assert_eq!(Token::balance_of(BASE), base_balance + 5);
+		});
+	}
+
+	#[test]
+	fn unrevealed_forfeit_goes_to_treasury() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 5_000, ForfeitDestination::Treasury(42)));
+
+			withdraw_after_missed_reveal(1);
+
+			assert_eq!(Token::balance_of(1), 995);
+			assert_eq!(Token::balance_of(42), 5);
+		});
+	}
+
+	#[test]
+	fn unrevealed_forfeit_is_burnt() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 5_000, ForfeitDestination::Burn));
+			let base_balance = Token::balance_of(BASE);
+
+			withdraw_after_missed_reveal(1);
+
+			assert_eq!(Token::balance_of(1), 995);
+			assert_eq!(Token::balance_of(BASE), base_balance);
+			assert_eq!(Token::total_supply(), 1_000_000 - 5);
+		});
+	}
+
+	#[test]
+	fn withdrawal_depends_on_the_round() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 5_000, ForfeitDestination::Base));
+			commit(1, 10);
+			commit(2, 20);
+			commit(3, 30);
+
+			// hash submission round, refunded in full
+			assert_ok!(Schelling::withdraw(Origin::signed(1), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(1), 1000);
+
+			// reveal round, rejected
+			System::set_block_number(51);
+			assert_noop!(Schelling::withdraw(Origin::signed(2), DEFAULT_TOPIC), "The reveal round is open, reveal the value or withdraw after the deadline");
+			go_to_reveal();
+			assert_noop!(Schelling::exit(Origin::signed(2)), "The reveal round is open, reveal the value or withdraw after the deadline");
+			assert_eq!(<Messages<Test>>::get(&DEFAULT_TOPIC, &2).deposit, DEPOSIT);
+
+			// after the deadline, refunded less the 50% forfeit
+			go_to_epoch_end();
+			assert_ok!(Schelling::withdraw(Origin::signed(2), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(2), 995);
+		});
+	}
+
+	#[test]
+	fn projected_reward_band_matches_the_resolution() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			for &who in accounts.iter() {
+				commit(who, 90 - who as i64 * 10);
+			}
+			go_to_reveal();
+			assert_eq!(Schelling::projected_reward_band(), (0, 0));
+			for &who in accounts.iter() {
+				assert_ok!(reveal(who, 90 - who as i64 * 10));
+			}
+
+			// values 10 to 80, indices 2 to 5 are rewarded
+			assert_eq!(Schelling::projected_reward_band(), (30, 60));
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			let rewarded: Vec<u64> = accounts.iter().cloned().filter(|who| Token::balance_of(who) == 1100).collect();
+			assert_eq!(rewarded, vec![3, 4, 5, 6]);
+		});
+	}
+
+	#[test]
+	fn extreme_values_resolve_through_saturation() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+			setup(&accounts);
+			assert_ok!(Schelling::set_weighted_rewards(root(), true));
+			<LifetimePenalties<Test>>::put(u64::max_value());
+
+			run_epoch(&[
+				(1, i64::min_value()), (2, i64::min_value()), (3, i64::min_value()), (4, i64::min_value()),
+				(5, i64::max_value()), (6, i64::max_value()), (7, i64::max_value()), (8, i64::max_value()), (9, i64::max_value()),
+			]);
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), i64::max_value());
+			assert_eq!(Schelling::lifetime_penalties(), u64::max_value());
+			// the furthest winner gets half the reward
+			assert_eq!(Token::balance_of(4), 1050);
+			assert_eq!(Token::balance_of(5), 1100);
+			assert_eq!(Token::balance_of(6), 1100);
+		});
+	}
+
+	#[test]
+	fn exit_returns_everything_and_allows_a_fresh_start() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::set_accrue_earnings(root(), true));
+			assert_ok!(Schelling::set_auto_rollover(Origin::signed(3), true));
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+			assert_eq!(Token::balance_of(3), 990);
+
+			assert_ok!(Schelling::exit(Origin::signed(3)));
+			assert_eq!(Token::balance_of(3), 1100);
+			assert_eq!(Token::locked_deposits(Schelling::rollover_key(&3)), 0);
+			assert_eq!(Schelling::standing_deposit(3), 0);
+			assert_eq!(Schelling::accrued_earnings(3), 0);
+			assert!(!Schelling::auto_rollover(3));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::AccountExited(3, 110))));
+
+			// a new commitment is locked from the balance again
+			commit(3, 30);
+			assert_eq!(Token::balance_of(3), 1090);
+		});
+	}
+
+	#[test]
+	fn fixed_point_feed_resolves_negative_fractions() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::set_feed_config(root(), 2, b"%".to_vec()));
+			assert_ok!(Schelling::set_fixed_point(root(), true));
+			// -1.25%, -0.50%, -3.75%, 0.25%
+			let reveals = [(1, -125i128), (2, -50), (3, -375), (4, 25)];
+			for &(who, value) in reveals.iter() {
+				assert_ok!(Schelling::submit_hash(Origin::signed(who), DEFAULT_TOPIC, commitment(who, value), DEPOSIT));
+			}
+			go_to_reveal();
+
+			assert_noop!(reveal(1, 1), "The feed expects fixed point values, use submit_fixed");
+			for &(who, value) in reveals.iter() {
+				assert_ok!(Schelling::submit_fixed(Origin::signed(who), DEFAULT_TOPIC, value, salt(who)));
+			}
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// sorted -3.75, -1.25, -0.50, 0.25, halfway between -1.25 and -0.50 rounded down
+			assert_eq!(Schelling::fixed_value(), -88);
+			assert_eq!(Token::balance_of(2), 1100);
+			assert_eq!(Token::balance_of(3), 999);
+		});
+	}
+
+	#[test]
+	fn has_revealed_flips_on_reveal_and_resets_each_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			for who in 1..5 {
+				commit(who, 10);
+			}
+			go_to_reveal();
+			assert!(!Schelling::has_revealed(DEFAULT_TOPIC, 1));
+
+			assert_ok!(reveal(1, 10));
+			assert!(Schelling::has_revealed(DEFAULT_TOPIC, 1));
+			assert!(!Schelling::has_revealed(DEFAULT_TOPIC, 2));
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert!(!Schelling::has_revealed(DEFAULT_TOPIC, 1));
+		});
+	}
+
+	#[test]
+	fn configure_applies_a_consistent_config() {
+		with_externalities(&mut new_test_ext(), || {
+			let config = SchellingConfig {
+				min_deposit: 20,
+				min_total_stake: 100,
+				hash_submission_blocks: 30,
+				value_submission_blocks: 40,
+				reward_per_submission: 70,
+				fixed_pool_reward: 300,
+				percentile_mode: PercentileMode::InclusiveCeil,
+				reward_tiers: vec![RewardTier { edge_bps: 5_000, multiplier_bps: 10_000 }],
+				weighted_rewards: true,
+				penalty_recycle_bps: 5_000,
+				stake_signal_mode: true,
+			};
+			assert_ok!(Schelling::configure(root(), config));
+
+			assert_eq!(Schelling::min_deposit(), 20);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::MinDepositChanged(20))));
+			assert_eq!(Schelling::min_total_stake(), 100);
+			assert_eq!(Schelling::hash_submission_blocks(DEFAULT_TOPIC), 30);
+			assert_eq!(Schelling::value_submission_blocks(DEFAULT_TOPIC), 40);
+			assert_eq!(Schelling::reward_per_submission(), 70);
+			assert_eq!(Schelling::fixed_pool_reward(), 300);
+			assert_eq!(Schelling::percentile_mode(), PercentileMode::InclusiveCeil);
+			assert_eq!(Schelling::reward_tiers().len(), 1);
+			assert!(Schelling::weighted_rewards());
+			assert_eq!(Schelling::penalty_recycle_bps(), 5_000);
+			assert!(Schelling::stake_signal_mode());
+		});
+	}
+
+	#[test]
+	fn inconsistent_config_is_rejected_wholesale() {
+		with_externalities(&mut new_test_ext(), || {
+			let config = SchellingConfig {
+				min_deposit: 20,
+				weighted_rewards: true,
+				reward_tiers: vec![
+					RewardTier { edge_bps: 7_500, multiplier_bps: 10_000 },
+					RewardTier { edge_bps: 2_500, multiplier_bps: 20_000 },
+				],
+				..Default::default()
+			};
+			assert_noop!(Schelling::configure(root(), config), "Tier edges should be strictly increasing");
+			assert_eq!(Schelling::min_deposit(), DEPOSIT);
+
+			let config = SchellingConfig { min_deposit: 0, ..Default::default() };
+			assert_noop!(Schelling::configure(root(), config), "The minimal deposit must not be zero");
+			assert_eq!(Schelling::min_deposit(), DEPOSIT);
+
+			let config = SchellingConfig { min_deposit: 20, hash_submission_blocks: 30, ..Default::default() };
+			assert_noop!(Schelling::configure(root(), config), "Round durations must not be zero");
+			assert_eq!(Schelling::min_deposit(), DEPOSIT);
+		});
+	}
+
+	#[test]
+	fn configured_round_durations_wait_for_the_next_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			let config = SchellingConfig {
+				min_deposit: DEPOSIT,
+				hash_submission_blocks: 30,
+				value_submission_blocks: 40,
+				reward_per_submission: 100,
+				..Default::default()
+			};
+			assert_ok!(Schelling::configure(root(), config));
+
+			// the running epoch keeps its rounds
+			assert_eq!(Schelling::hash_submission_blocks(DEFAULT_TOPIC), 50);
+			assert_eq!(Schelling::pending_round_durations(DEFAULT_TOPIC), Some((30, 40)));
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::hash_submission_blocks(DEFAULT_TOPIC), 30);
+			assert_eq!(Schelling::value_submission_blocks(DEFAULT_TOPIC), 40);
+			assert_eq!(Schelling::pending_round_durations(DEFAULT_TOPIC), None);
+		});
+	}
+
+	#[test]
+	fn invalidated_epoch_carries_the_previous_value_forward() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+			assert!(!Schelling::value_carried());
+
+			assert_ok!(Schelling::set_min_total_stake(root(), 1_000));
+			run_epoch(&[(1, 50), (2, 60), (3, 70), (4, 80)]);
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+			assert!(Schelling::value_carried());
+			assert!(events().contains(&TestEvent::schelling(RawEvent::ValueCarriedForward(25))));
+		});
+	}
+
+	#[test]
+	fn direct_submissions_aggregate_when_enabled() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_noop!(
+				Schelling::submit_direct_value(Origin::signed(1), DEFAULT_TOPIC, 10, DEPOSIT),
+				"Direct submission is disabled, commit a hash first"
+			);
+
+			assert_ok!(Schelling::set_direct_submit_mode(root(), true));
+			assert_ok!(Schelling::set_max_deposit(root(), DEPOSIT * 3));
+			assert_noop!(
+				Schelling::submit_direct_value(Origin::signed(1), DEFAULT_TOPIC, 10, DEPOSIT * 4),
+				"The deposit exceeds the maximal deposit"
+			);
+			for who in 1..5 {
+				assert_ok!(Schelling::submit_direct_value(Origin::signed(who), DEFAULT_TOPIC, who as i64 * 10, DEPOSIT));
+			}
+			assert_noop!(
+				Schelling::submit_direct_value(Origin::signed(1), DEFAULT_TOPIC, 10, DEPOSIT),
+				"A value was already submitted this epoch"
+			);
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+			assert_eq!(Token::balance_of(3), 1100);
+			assert_eq!(Token::balance_of(1), 999);
+		});
+	}
+
+	#[test]
+	fn late_reveals_earn_less() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			assert_ok!(Schelling::set_copy_guard(root(), 5_000, 30));
+			for &who in accounts.iter() {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			for &who in accounts.iter().filter(|who| **who != 5) {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+
+			// the last block of the reveal round
+			System::set_block_number(100);
+			assert_ok!(reveal(5, 50));
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(4), 1100);
+			assert_eq!(Token::balance_of(5), 1050);
+			assert_eq!(Token::balance_of(6), 1100);
+		});
+	}
+
+	#[test]
+	fn epoch_payout_is_capped_proportionally() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			assert_ok!(Schelling::set_percentile_mode(root(), PercentileMode::InclusiveFloor));
+			assert_ok!(Schelling::set_max_epoch_payout(root(), 250));
+			let base_balance = Token::balance_of(BASE);
+
+			// 5 winners would get 500 uncapped
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60), (7, 70), (8, 80)]);
+
+			for who in 3..8 {
+				assert_eq!(Token::balance_of(who), 1050);
+			}
+			// paid out 250, collected 3 penalties
+			assert_eq!(Token::balance_of(BASE), base_balance - 250 + 3);
+		});
+	}
+
+	#[test]
+	fn dataset_hash_matches_the_sorted_values() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			run_epoch(&[(1, 40), (2, 10), (3, 30), (4, 20)]);
+
+			let expected = vec![10i64, 20, 30, 40].using_encoded(BlakeTwo256::hash);
+			assert_eq!(Schelling::last_dataset_hash(), expected);
+		});
+	}
+
+	#[test]
+	fn registered_consumers_are_notified() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::add_consumer(root(), 70));
+			assert_ok!(Schelling::add_consumer(root(), 71));
+			assert_ok!(Schelling::add_consumer(root(), 72));
+			assert_ok!(Schelling::remove_consumer(root(), 72));
+			assert_noop!(Schelling::add_consumer(root(), 70), "The consumer is already registered");
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+
+			NOTIFIED.with(|notified| assert_eq!(*notified.borrow(), vec![(70, 25), (71, 25)]));
+		});
+	}
+
+	#[test]
+	fn low_reveal_ratio_invalidates_the_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts: Vec<u64> = (1..=100).collect();
+			setup(&accounts);
+			assert_ok!(Schelling::set_min_reveal_ratio_bps(root(), 5_000));
+			for &who in accounts.iter() {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			for who in 1..=10 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// 10% revealed against 50% required
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::EpochInvalidated(1, 100))));
+			for who in 1..=10 {
+				assert_eq!(Token::balance_of(who), 1000);
+			}
+		});
+	}
+
+	#[test]
+	fn value_set_block_is_the_resolution_block() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+
+			assert_eq!(Schelling::value_set_block(), 102);
+		});
+	}
+
+	#[test]
+	fn salted_commitment_is_revealed() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			commit(1, 42);
+			go_to_reveal();
+
+			assert_ok!(reveal(1, 42));
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 1);
+		});
+	}
+
+	#[test]
+	fn reveal_not_matching_the_commitment_is_rejected() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			commit(1, 42);
+			go_to_reveal();
+
+			assert_noop!(reveal(1, 43), "The revealed value does not match the committed hash");
+			assert_noop!(
+				Schelling::submit_value(Origin::signed(1), DEFAULT_TOPIC, 42, salt(2)),
+				"The revealed value does not match the committed hash"
+			);
+			assert!(<Messages<Test>>::exists(&DEFAULT_TOPIC, &1));
+		});
+	}
+
+	#[test]
+	fn smoothing_dampens_a_median_jump() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_smoothing_bps(root(), 7_500));
+
+			// the first resolution is not smoothed
+			run_epoch(&[(1, 100), (2, 100), (3, 100)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 100);
+
+			// the crowd median jumps to 1000, a quarter of the jump goes through
+			run_epoch(&[(1, 1000), (2, 1000), (3, 1000)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 325);
+		});
+	}
+
+	#[test]
+	fn epoch_is_settled_on_finalize() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			for who in 1..=3 {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			for who in 1..=3 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+
+			// nothing happens before the end of the reveal round
+			System::set_block_number(100);
+			<Schelling as OnFinalize<u64>>::on_finalize(100);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+
+			System::set_block_number(101);
+			<Schelling as OnFinalize<u64>>::on_finalize(101);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+			assert_eq!(Token::balance_of(2), 1100);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 101);
+			assert!(Schelling::epoch_active(DEFAULT_TOPIC));
+		});
+	}
+
+	#[test]
+	fn failed_settlement_on_finalize_emits_an_event() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			commit(1, 10);
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			<TokenBase<Test>>::kill();
+
+			System::set_block_number(101);
+			<Schelling as OnFinalize<u64>>::on_finalize(101);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::AutoResolutionFailed(101))));
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 1);
+		});
+	}
+
+	#[test]
+	fn epoch_dominated_by_one_funder_is_rejected() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			assert_ok!(Schelling::set_max_funder_share_bps(root(), 5_000));
+			for who in 1..=3 {
+				assert_ok!(Schelling::set_funder(root(), who, Some(9)));
+			}
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]);
+
+			// 30 of the 50 revealed are funded by account 9
+			assert!(events().contains(&TestEvent::schelling(RawEvent::FunderDominance(9, 30))));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+			assert_eq!(Token::balance_of(3), 1000);
+		});
+	}
+
+	#[test]
+	fn funder_below_the_share_does_not_flag_the_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			assert_ok!(Schelling::set_max_funder_share_bps(root(), 5_000));
+			for who in 1..=2 {
+				assert_ok!(Schelling::set_funder(root(), who, Some(9)));
+			}
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]);
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 30);
+		});
+	}
+
+	#[test]
+	fn epoch_without_reveals_rolls_over() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			commit(1, 10);
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+			assert_eq!(Schelling::value_version(), 0);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 102);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::EpochEndedWithNoValue(102))));
+		});
+	}
+
+	#[test]
+	fn single_reveal_sets_the_value_without_penalty() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			run_epoch(&[(1, 10)]);
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 10);
+			assert_eq!(Token::balance_of(1), 1000);
+		});
+	}
+
+	#[test]
+	fn two_reveals_set_the_value_without_penalty() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2]);
+			run_epoch(&[(1, 10), (2, 20)]);
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 15);
+			assert_eq!(Token::balance_of(1), 1000);
+			assert_eq!(Token::balance_of(2), 1000);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 102);
+		});
+	}
+
+	#[test]
+	fn epoch_state_reflects_the_current_epoch() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(Schelling::epoch_state().phase, EpochPhase::Inactive);
+
+			setup(&[1, 2, 3]);
+			commit(1, 10);
+			commit(2, 20);
+			commit_with(3, 30, 20);
+			assert_eq!(Schelling::epoch_state().phase, EpochPhase::Commit);
+
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 20));
+			System::set_block_number(System::block_number() + 10);
+
+			assert_eq!(Schelling::epoch_state(), EpochState {
+				epoch_start: 1,
+				phase: EpochPhase::Reveal,
+				committed: 3,
+				revealed: 2,
+				total_locked: 40,
+				blocks_remaining: 30,
+			});
+
+			go_to_epoch_end();
+			let state = Schelling::epoch_state();
+			assert_eq!(state.phase, EpochPhase::Resolution);
+			assert_eq!(state.blocks_remaining, 0);
+		});
+	}
+
+	#[test]
+	fn short_epoch_runs_end_to_end() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_ok!(Schelling::set_round_durations(root(), DEFAULT_TOPIC, 5, 5));
+			setup(&[1, 2, 3]);
+			commit(1, 10);
+			commit(2, 20);
+			commit(3, 30);
+
+			System::set_block_number(6);
+			assert_noop!(Schelling::submit_hash(Origin::signed(4), DEFAULT_TOPIC, H256::zero(), DEPOSIT), "The deadline for hash submission is passed, try next epoch");
+			System::set_block_number(7);
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 20));
+			assert_ok!(reveal(3, 30));
+			assert_noop!(Schelling::send_rewards(root(), DEFAULT_TOPIC), "It's not the time to send out the rewards yet");
+
+			System::set_block_number(11);
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 11);
+		});
+	}
+
+	#[test]
+	fn round_durations_change_from_the_next_epoch_on() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_noop!(Schelling::set_round_durations(root(), DEFAULT_TOPIC, 0, 5), "Round durations must not be zero");
+			assert_noop!(Schelling::set_round_durations(Origin::signed(1), DEFAULT_TOPIC, 5, 5), "bad origin: expected to be a root origin");
+
+			assert_ok!(Schelling::set_round_durations(root(), DEFAULT_TOPIC, 5, 5));
+			assert_eq!(Schelling::hash_submission_blocks(DEFAULT_TOPIC), 50);
+			assert_eq!(Schelling::blocks_until_resolution(), 100);
+
+			// the in-flight round keeps its deadlines
+			System::set_block_number(20);
+			commit(1, 10);
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::hash_submission_blocks(DEFAULT_TOPIC), 5);
+			assert_eq!(Schelling::value_submission_blocks(DEFAULT_TOPIC), 5);
+			assert_eq!(Schelling::pending_round_durations(DEFAULT_TOPIC), None);
+			assert_eq!(Schelling::blocks_until_resolution(), 10);
+		});
+	}
+
+	#[test]
+	fn genesis_config_is_readable() {
+		with_externalities(&mut new_test_ext_with(7, 25, 40), || {
+			assert_eq!(Schelling::token_base(), 7);
+			assert_eq!(Schelling::min_deposit(), 25);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 40);
+		});
+	}
+
+	#[test]
+	fn genesis_epoch_start_opens_the_first_epoch() {
+		with_externalities(&mut new_test_ext_with(BASE, DEPOSIT, 40), || {
+			assert!(Schelling::epoch_active(DEFAULT_TOPIC));
+			assert_ok!(Token::init(BASE));
+			for who in 1..4 {
+				assert_ok!(Token::transfer(Origin::signed(BASE), who, 1000));
+				assert_ok!(Token::approve(Origin::signed(BASE), who, 1000));
+			}
+
+			System::set_block_number(40);
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+			assert_eq!(Token::balance_of(2), 1100);
+		});
+	}
+
+	#[test]
+	#[should_panic(expected = "The genesis min_deposit must not be zero")]
+	fn genesis_min_deposit_must_not_be_zero() {
+		new_test_ext_with(BASE, 0, 0);
+	}
+
+	#[test]
+	fn long_term_lockers_earn_a_bonus() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			assert_ok!(Schelling::set_long_term_bonus_bps(root(), 1_000));
+			assert_noop!(Schelling::submit_locked_hash(Origin::signed(4), DEFAULT_TOPIC, commitment(4, 40), DEPOSIT, 0), "Invalid lock duration");
+
+			assert_ok!(Schelling::submit_locked_hash(Origin::signed(4), DEFAULT_TOPIC, commitment(4, 40), DEPOSIT, 3));
+			for &who in accounts.iter().filter(|who| **who != 4) {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			for &who in accounts.iter() {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// both inside the reward band, the locker gets 10% more for staying locked past the epoch
+			assert_eq!(Token::balance_of(5), 1100);
+			assert_eq!(Token::balance_of(4), 990 + 110);
+
+			// the deposit stays locked for the two remaining epochs
+			assert_eq!(Schelling::standing_deposit(4), DEPOSIT);
+			assert_eq!(Schelling::locked_epochs(DEFAULT_TOPIC, &4), 2);
+			assert_noop!(Schelling::exit(Origin::signed(4)), "The deposit is locked for more epochs");
+
+			// a withdrawal does not end the lock
+			commit(4, 40);
+			assert_ok!(Schelling::withdraw(Origin::signed(4), DEFAULT_TOPIC));
+			assert_eq!(Schelling::standing_deposit(4), DEPOSIT);
+			assert_eq!(Token::balance_of(4), 1100);
+
+			// the lock counts down with the epochs even without taking part
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Schelling::locked_epochs(DEFAULT_TOPIC, &4), 1);
+			assert_noop!(Schelling::exit(Origin::signed(4)), "The deposit is locked for more epochs");
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Schelling::locked_epochs(DEFAULT_TOPIC, &4), 0);
+			assert_ok!(Schelling::exit(Origin::signed(4)));
+			assert_eq!(Token::balance_of(4), 1100 + DEPOSIT);
+		});
+	}
+
+	#[test]
+	fn last_slashed_lists_the_out_of_band_submitters() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60), (7, 70), (8, 80)]);
+
+			assert_eq!(Schelling::last_slashed(), vec![1, 2, 7, 8]);
+			assert_eq!(Schelling::last_slashed_page(0, 2), vec![1, 2]);
+			assert_eq!(Schelling::last_slashed_page(1, 2), vec![7, 8]);
+			assert_eq!(Schelling::last_slashed_page(2, 2), Vec::<u64>::new());
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+			assert_eq!(Schelling::last_slashed(), vec![1, 4]);
+		});
+	}
+
+	#[test]
+	fn min_deposit_and_token_base_are_set_by_root() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2]);
+			commit(1, 10);
+
+			assert_ok!(Schelling::set_min_deposit(root(), DEPOSIT * 2));
+			assert_ok!(Schelling::set_token_base(root(), 2));
+			assert_eq!(Schelling::min_deposit(), DEPOSIT * 2);
+			assert_eq!(Schelling::token_base(), 2);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::MinDepositChanged(DEPOSIT * 2))));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::TokenBaseChanged(2))));
+
+			// the locked deposit is kept, new commitments need the new minimum
+			assert_eq!(<Messages<Test>>::get(&DEFAULT_TOPIC, &1).deposit, DEPOSIT);
+			assert_noop!(Schelling::submit_hash(Origin::signed(2), DEFAULT_TOPIC, commitment(2, 20), DEPOSIT), "The deposit is not high enough");
+		});
+	}
+
+	#[test]
+	fn min_deposit_and_token_base_updates_are_checked() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+
+			assert_noop!(Schelling::set_min_deposit(root(), 0), "The minimal deposit must not be zero");
+			assert_noop!(Schelling::set_min_deposit(Origin::signed(1), DEPOSIT), "bad origin: expected to be a root origin");
+			assert_noop!(Schelling::set_token_base(Origin::signed(1), 1), "bad origin: expected to be a root origin");
+			assert_eq!(Schelling::min_deposit(), DEPOSIT);
+			assert_eq!(Schelling::token_base(), BASE);
+		});
+	}
+
+	#[test]
+	fn deposit_is_increased_during_the_hash_round_up_to_the_cap() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_ok!(Schelling::set_max_deposit(root(), DEPOSIT * 3));
+			commit(1, 10);
+
+			assert_ok!(Schelling::increase_deposit(Origin::signed(1), DEFAULT_TOPIC, DEPOSIT));
+			assert_eq!(<Messages<Test>>::get(&DEFAULT_TOPIC, &1).deposit, DEPOSIT * 2);
+			assert_eq!(Token::balance_of(1), 1000 - DEPOSIT * 2);
+
+			assert_noop!(Schelling::increase_deposit(Origin::signed(1), DEFAULT_TOPIC, DEPOSIT * 2), "The deposit exceeds the maximal deposit");
+			go_to_reveal();
+			assert_noop!(Schelling::increase_deposit(Origin::signed(1), DEFAULT_TOPIC, DEPOSIT), "Deposits can only be increased during the hash submission round");
+		});
+	}
+
+	#[test]
+	fn withdrawal_in_the_hash_round_is_refunded_in_full() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 1_000, ForfeitDestination::Base));
+			commit(1, 10);
+
+			assert_ok!(Schelling::withdraw(Origin::signed(1), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(1), 1000);
+			assert!(!events().iter().any(|event| match event {
+				TestEvent::schelling(RawEvent::NonRevealPenalized(..)) => true,
+				_ => false,
+			}));
+		});
+	}
+
+	#[test]
+	fn withdrawal_after_the_value_deadline_is_penalized() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 1_000, ForfeitDestination::Base));
+			let base_balance = Token::balance_of(BASE);
+			commit(1, 10);
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::withdraw(Origin::signed(1), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(1), 999);
+			assert_eq!(Token::balance_of(BASE), base_balance + 1);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::NonRevealPenalized(1, 1))));
+		});
+	}
+
+	#[test]
+	fn first_revealer_is_paid_a_bonus() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_first_revealer_bonus(root(), 5));
+			commit(1, 10);
+			commit(2, 20);
+			commit(3, 30);
+
+			go_to_reveal();
+			assert_ok!(reveal(3, 30));
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 20));
+			assert_eq!(Schelling::first_revealer(DEFAULT_TOPIC), Some(3));
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(1), 999);
+			assert_eq!(Token::balance_of(2), 1100);
+			assert_eq!(Token::balance_of(3), 999 + 5);
+			assert_eq!(Schelling::first_revealer(DEFAULT_TOPIC), None);
+		});
+	}
+
+	#[test]
+	fn config_changes_apply_from_the_next_epoch_once_committed() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			for &who in accounts.iter() {
+				commit(who, who as i64 * 10);
+			}
+			assert_ok!(Schelling::set_percentile_mode(root(), PercentileMode::InclusiveCeil));
+			assert_ok!(Schelling::set_reward_per_submission(root(), 200));
+			assert_ok!(Schelling::set_fixed_pool_reward(root(), 1_000));
+			assert_eq!(Schelling::config_snapshot(DEFAULT_TOPIC).map(|config| config.percentile_mode), Some(PercentileMode::Interpolated));
+			assert_eq!(Schelling::config_snapshot(DEFAULT_TOPIC).map(|config| config.reward_per_submission), Some(100));
+
+			go_to_reveal();
+			for &who in accounts.iter() {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// resolved with the default band, accounts 3 to 6
+			let balances: Vec<u64> = accounts.iter().map(|who| Token::balance_of(who)).collect();
+			assert_eq!(balances, vec![999, 999, 1100, 1100, 1100, 1100, 999, 999]);
+			assert_eq!(Schelling::config_snapshot(DEFAULT_TOPIC), None);
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 8), Ok((1, 6)));
+		});
+	}
+
+	#[test]
+	fn required_stake_moves_the_weighted_median_to_the_target() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_eq!(Schelling::required_stake_for(40), DEPOSIT);
+			assert_ok!(Schelling::set_weighted_mode(root(), true));
+
+			commit_with(1, 10, DEPOSIT);
+			commit_with(2, 20, DEPOSIT * 3);
+			commit_with(3, 40, 41);
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_ok!(reveal(2, 20));
+
+			// 40 tokens revealed below the target, 40 more would only tie them
+			assert_eq!(Schelling::required_stake_for(40), 41);
+			assert_eq!(Schelling::required_stake_for(20), DEPOSIT);
+
+			assert_ok!(reveal(3, 40));
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 40);
+		});
+	}
+
+	#[test]
+	fn value_history_keeps_the_last_epochs() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_ok!(Schelling::set_max_history_length(root(), 3));
+
+			let mut epochs = Vec::new();
+			for value in 1..=4 {
+				assert_eq!(Schelling::history_len(), epochs.len().min(3) as u32);
+				epochs.push(Schelling::epoch_start(DEFAULT_TOPIC));
+				run_epoch(&[(1, value * 10)]);
+			}
+			assert!(events().contains(&TestEvent::schelling(RawEvent::NewValueSet(epochs[3], 40))));
+
+			// the first epoch is pruned
+			assert_eq!(Schelling::value_history(), vec![(epochs[1], 20), (epochs[2], 30), (epochs[3], 40)]);
+			assert_eq!(Schelling::history_len(), 3);
+
+			// a shorter history is pruned on the next resolution
+			assert_ok!(Schelling::set_max_history_length(root(), 1));
+			run_epoch(&[(1, 50)]);
+			assert_eq!(Schelling::history_len(), 1);
+		});
+	}
+
+	#[test]
+	fn token_base_can_not_commit() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[]);
+
+			assert_noop!(
+				Schelling::submit_hash(Origin::signed(BASE), DEFAULT_TOPIC, commitment(BASE, 10), DEPOSIT),
+				"The token_base account can not participate"
+			);
+			assert_ok!(Schelling::set_direct_submit_mode(root(), true));
+			assert_noop!(
+				Schelling::submit_direct_value(Origin::signed(BASE), DEFAULT_TOPIC, 10, DEPOSIT),
+				"The token_base account can not participate"
+			);
+		});
+	}
+
+	#[test]
+	fn odd_count_resolves_to_the_middle_value() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			run_epoch(&[(1, 10), (2, 20), (3, 31), (4, 40), (5, 50)]);
+
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 31);
+		});
+	}
+
+	#[test]
+	fn even_count_resolves_to_the_mean_of_the_middle_values() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5, 6]);
+			run_epoch(&[(1, 10), (2, 20), (3, 31), (4, 40), (5, 50), (6, 60)]);
+
+			// (31 + 40) / 2 rounded down
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 35);
+			assert_eq!(Schelling::resolve(&[i64::max_value() - 1, i64::max_value()]), Some(i64::max_value() - 1));
+			assert_eq!(Schelling::resolve(&[i64::min_value(), i64::max_value()]), Some(-1));
+		});
+	}
+
+	#[test]
+	fn negative_and_positive_values_resolve_to_the_signed_median() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			let submissions = [(1, -40), (2, 15), (3, -5), (4, 0), (5, -20)];
+			for &(who, value) in submissions.iter() {
+				commit(who, value);
+			}
+			go_to_reveal();
+
+			// the commitment binds the sign of the value
+			assert_noop!(reveal(2, -15), "The revealed value does not match the committed hash");
+			for &(who, value) in submissions.iter() {
+				assert_ok!(reveal(who, value));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// sorted -40, -20, -5, 0, 15
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), -5);
+			assert_eq!(Schelling::last_sorted_values(), vec![-40, -20, -5, 0, 15]);
+			assert_eq!(Schelling::value_history(), vec![(1, -5)]);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::NewValueSet(1, -5))));
+			for &who in [3, 4, 5].iter() {
+				assert_eq!(Token::balance_of(who), 1100);
+			}
+			assert_eq!(Token::balance_of(1), 999);
+			assert_eq!(Token::balance_of(2), 999);
+			assert_eq!(Schelling::accuracy((DEFAULT_TOPIC, 1)), Accuracy { epochs: 1, average_distance: 35 });
+
+			// sorted -30, -10, -5, 20, halfway between -10 and -5 rounded down
+			run_epoch(&[(1, -10), (2, -5), (3, -30), (4, 20)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), -8);
+		});
+	}
+
+	#[test]
+	fn reward_per_submission_is_configurable() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Token::balance_of(2), 1100);
+
+			assert_ok!(Schelling::set_reward_per_submission(root(), 40));
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Token::balance_of(2), 1140);
+		});
+	}
+
+	#[test]
+	fn underfunded_token_base_scales_rewards_down() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			// 4 winners would get 400
+			let base_balance = Token::balance_of(BASE);
+			assert_ok!(Token::transfer(Origin::signed(BASE), 99, base_balance - 200));
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50), (6, 60), (7, 70), (8, 80)]);
+
+			for who in 3..7 {
+				assert_eq!(Token::balance_of(who), 1050);
+			}
+			assert_eq!(Token::balance_of(1), 999);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::InsufficientRewardFunds(400, 200))));
+		});
+	}
+
+	#[test]
+	fn accuracy_improves_with_closer_values() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+
+			run_epoch(&[(1, 10), (2, 20), (3, 40)]);
+			assert_eq!(Schelling::accuracy((DEFAULT_TOPIC, 3)), Accuracy { epochs: 1, average_distance: 20 });
+
+			run_epoch(&[(1, 10), (2, 20), (3, 20)]);
+			assert_eq!(Schelling::accuracy((DEFAULT_TOPIC, 3)), Accuracy { epochs: 2, average_distance: 10 });
+
+			run_epoch(&[(1, 10), (2, 20), (3, 20)]);
+			assert_eq!(Schelling::accuracy((DEFAULT_TOPIC, 3)), Accuracy { epochs: 3, average_distance: 6 });
+			assert_eq!(Schelling::accuracy((DEFAULT_TOPIC, 2)), Accuracy { epochs: 3, average_distance: 0 });
+		});
+	}
+
+	#[test]
+	fn interpolated_band_pins_rewarded_indices() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 0), Ok((0, 0)));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 2), Ok((0, 0)));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 3), Ok((1, 2)));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 4), Ok((1, 3)));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 5), Ok((1, 4)));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 8), Ok((2, 6)));
+			assert_eq!(Schelling::reward_band(DEFAULT_TOPIC, 100), Ok((25, 75)));
+		});
+	}
+
+	#[test]
+	fn interpolated_band_rewards_the_median_cluster() {
+		// (participants, rewarded accounts), account `i` reports `i * 10`
+		let cases: [(u64, &[u64]); 3] = [(4, &[2, 3]), (5, &[2, 3, 4]), (8, &[3, 4, 5, 6])];
+		for &(size, expected) in cases.iter() {
+			with_externalities(&mut new_test_ext(), || {
+				let accounts: Vec<u64> = (1..=size).collect();
+				setup(&accounts);
+				let submissions: Vec<(u64, i64)> = accounts.iter().map(|&who| (who, who as i64 * 10)).collect();
+				run_epoch(&submissions);
+
+				let rewarded: Vec<u64> = accounts.iter().cloned().filter(|who| Token::balance_of(who) == 1100).collect();
+				let penalized: Vec<u64> = accounts.iter().cloned().filter(|who| Token::balance_of(who) == 999).collect();
+				let outside: Vec<u64> = accounts.iter().cloned().filter(|who| !expected.contains(who)).collect();
+				assert_eq!(rewarded, expected.to_vec());
+				assert_eq!(penalized, outside);
+			});
+		}
+	}
+
+	#[test]
+	fn interpolated_band_rewards_half_of_a_hundred() {
+		with_externalities(&mut new_test_ext(), || {
+			// keeps clear of the token_base account
+			let accounts: Vec<u64> = (101..=200).collect();
+			setup(&accounts);
+			let submissions: Vec<(u64, i64)> = accounts.iter().map(|&who| (who, who as i64)).collect();
+			run_epoch(&submissions);
+
+			// indices 25 to 74
+			let rewarded: Vec<u64> = accounts.iter().cloned().filter(|who| Token::balance_of(who) == 1100).collect();
+			assert_eq!(rewarded, (126..=175).collect::<Vec<u64>>());
+			assert_eq!(Schelling::last_slashed().len(), 50);
+		});
+	}
+
+	#[test]
+	fn epoch_phase_counts_down_each_round() {
+		with_externalities(&mut new_test_ext(), || {
+			assert_eq!(Schelling::epoch_phase(), (EpochPhase::Inactive, 0));
+			assert_noop!(Schelling::force_send_rewards(root(), DEFAULT_TOPIC), "There is no active epoch to settle");
+
+			setup(&[1]);
+			assert_eq!(Schelling::epoch_phase(), (EpochPhase::Commit, 51));
+			System::set_block_number(51);
+			assert_eq!(Schelling::epoch_phase(), (EpochPhase::Commit, 1));
+
+			System::set_block_number(52);
+			assert_eq!(Schelling::epoch_phase(), (EpochPhase::Reveal, 49));
+			System::set_block_number(100);
+			assert_eq!(Schelling::epoch_phase(), (EpochPhase::Reveal, 1));
+
+			System::set_block_number(101);
+			assert_eq!(Schelling::epoch_phase(), (EpochPhase::Resolution, 0));
+			System::set_block_number(150);
+			assert_eq!(Schelling::epoch_phase(), (EpochPhase::Resolution, 0));
+
+			assert_ok!(Schelling::set_round_durations(root(), DEFAULT_TOPIC, 10, 20));
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::epoch_phase(), (EpochPhase::Commit, 10));
+			assert_eq!(Schelling::min_deposit(), DEPOSIT);
+		});
+	}
+
+	#[test]
+	fn recommit_reuses_the_pending_deposit() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_noop!(Schelling::recommit(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 20i64), DEPOSIT), "Message hash was not submitted");
+
+			commit(1, 10);
+			go_to_epoch_end();
+			assert_ok!(Schelling::force_new_epoch(root(), DEFAULT_TOPIC));
+			let epoch_start = Schelling::epoch_start(DEFAULT_TOPIC);
+
+			// the new deposit is higher, the difference is locked from the balance
+			assert_noop!(Schelling::recommit(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 20i64), 2000), "Not enough balance.");
+			assert_ok!(Schelling::recommit(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 20i64), 15));
+			assert_eq!(Token::balance_of(1), 985);
+			assert_eq!(Token::locked_deposits(commitment(1, 10i64)), 0);
+			assert_eq!(Token::locked_deposits(commitment(1, 20i64)), 15);
+			assert_eq!(Schelling::standing_deposit(1), 0);
+			assert_eq!(<Messages<Test>>::get(&DEFAULT_TOPIC, &1).epoch, epoch_start);
+			assert_eq!(Schelling::committers(DEFAULT_TOPIC), vec![1]);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::DepositWithdrawn(1, DEPOSIT))));
+
+			go_to_reveal();
+			assert_ok!(reveal(1, 20));
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 1);
+		});
+	}
+
+	#[test]
+	fn recommit_with_a_lower_deposit_refunds_the_rest() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			commit_with(1, 10, 20);
+
+			assert_ok!(Schelling::recommit(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 30i64), DEPOSIT));
+			assert_eq!(Token::balance_of(1), 990);
+			assert_eq!(Token::locked_deposits(commitment(1, 10i64)), 0);
+			assert_eq!(Token::locked_deposits(commitment(1, 30i64)), DEPOSIT);
+
+			go_to_reveal();
+			assert_noop!(
+				Schelling::recommit(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 40i64), DEPOSIT),
+				"The reveal round is open, reveal the value or withdraw after the deadline"
+			);
+		});
+	}
+
+	#[test]
+	fn update_hash_replaces_the_commitment() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			commit(1, 10);
+
+			assert_ok!(Schelling::update_hash(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 20i64), 25));
+			assert_eq!(Token::balance_of(1), 975);
+			assert_eq!(Token::locked_deposits(commitment(1, 10i64)), 0);
+			assert_eq!(Token::locked_deposits(commitment(1, 20i64)), 25);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::HashUpdated(1, commitment(1, 20i64), 25))));
+
+			assert_ok!(Schelling::update_hash(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 30i64), DEPOSIT));
+			assert_eq!(Token::balance_of(1), 990);
+			assert_eq!(Token::locked_deposits(commitment(1, 20i64)), 0);
+			assert_eq!(Token::locked_deposits(commitment(1, 30i64)), DEPOSIT);
+
+			// only the new hash can be revealed
+			go_to_reveal();
+			assert_noop!(reveal(1, 20), "The revealed value does not match the committed hash");
+			assert_ok!(reveal(1, 30));
+		});
+	}
+
+	#[test]
+	fn update_hash_after_the_hash_deadline_is_rejected() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			assert_noop!(Schelling::update_hash(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 20i64), DEPOSIT), "Message hash was not submitted");
+			commit(1, 10);
+
+			System::set_block_number(51);
+			assert_noop!(
+				Schelling::update_hash(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 20i64), DEPOSIT),
+				"The deadline for hash submission is passed, try next epoch"
+			);
+			assert_eq!(Token::locked_deposits(commitment(1, 10i64)), DEPOSIT);
+		});
+	}
+
+	#[test]
+	fn submissions_past_their_deadline_are_refused_before_dispatch() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2]);
+			let late_commit = Call::<Test>::submit_hash(DEFAULT_TOPIC, commitment(2, 20i64), DEPOSIT);
+			let reveal_call = Call::<Test>::submit_value(DEFAULT_TOPIC, 10, salt(1));
+			assert_ok!(Schelling::precheck_submission(&late_commit, &2));
+			assert_eq!(Schelling::precheck_submission(&reveal_call, &1), Err("Message hash was not submitted"));
+			commit(1, 10);
+
+			// the hash submission round is over
+			System::set_block_number(51);
+			assert_eq!(Schelling::precheck_submission(&late_commit, &2), Err("The deadline for hash submission is passed, try next epoch"));
+			assert_eq!(Schelling::precheck_submission(&reveal_call, &1), Err("Hash submission round did not end yet"));
+
+			go_to_reveal();
+			assert_ok!(Schelling::precheck_submission(&reveal_call, &1));
+			// other calls are left to their dispatch
+			assert_ok!(Schelling::precheck_submission(&Call::<Test>::withdraw(DEFAULT_TOPIC), &2));
+
+			go_to_epoch_end();
+			assert_eq!(
+				Schelling::precheck_submission(&reveal_call, &1),
+				Err("The deadline for value submission is passed, please withdraw deposit")
+			);
+			// nothing was dispatched, the commitment is still pending
+			assert_eq!(Token::balance_of(1), 1000 - DEPOSIT);
+			assert!(!Schelling::has_revealed(DEFAULT_TOPIC, 1));
+		});
+	}
+
+	#[test]
+	fn paused_commits_still_allow_reveals_and_withdrawals() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			commit(1, 10);
+			commit(2, 20);
+			assert_ok!(Schelling::set_commits_paused(root(), true));
+
+			assert_noop!(
+				Schelling::submit_hash(Origin::signed(3), DEFAULT_TOPIC, commitment(3, 30i64), DEPOSIT),
+				"New commitments are paused"
+			);
+			// the pending commitment is not withdrawn for a recommit that can not go through
+			assert_noop!(
+				Schelling::recommit(Origin::signed(1), DEFAULT_TOPIC, commitment(1, 11i64), DEPOSIT),
+				"New commitments are paused"
+			);
+			assert_ok!(Schelling::withdraw(Origin::signed(2), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(2), 1000);
+
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 1);
+
+			assert_ok!(Schelling::set_commits_paused(root(), false));
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			commit(3, 30);
+		});
+	}
+
+	#[test]
+	fn unrevealed_deposits_are_slashed_on_settlement() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2]);
+			assert_ok!(Schelling::set_penalty_recycle_bps(root(), 5_000));
+			let base_balance = Token::balance_of(BASE);
+			commit(1, 10);
+			commit(2, 20);
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(1), 1000);
+			assert_eq!(Token::balance_of(2), 1000 - DEPOSIT);
+			assert_eq!(Token::balance_of(BASE), base_balance + DEPOSIT);
+			assert_eq!(Token::locked_deposits(commitment(2, 20i64)), 0);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::NonRevealSlashed(2, DEPOSIT))));
+			assert_eq!(Schelling::last_slashed(), vec![2]);
+			assert_eq!(Schelling::lifetime_penalties(), DEPOSIT);
+			assert_eq!(Schelling::reward_pool(DEFAULT_TOPIC), DEPOSIT / 2);
+
+			assert!(!<Messages<Test>>::exists(&DEFAULT_TOPIC, &2));
+			assert!(Schelling::committers(DEFAULT_TOPIC).is_empty());
+			commit(2, 20);
+		});
+	}
+
+	#[test]
+	fn unrevealed_deposits_are_slashed_to_the_forfeit_destination() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 5_000, ForfeitDestination::Treasury(42)));
+			assert_ok!(Schelling::set_penalty_recycle_bps(root(), 5_000));
+			commit(1, 10);
+			commit(2, 20);
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(42), DEPOSIT / 2);
+			assert_eq!(Token::balance_of(2), 1000 - DEPOSIT / 2);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::DepositReturned(2, DEPOSIT / 2))));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::NonRevealSlashed(2, DEPOSIT / 2))));
+			assert_eq!(Schelling::lifetime_penalties(), DEPOSIT / 2);
+			// the slashed deposit did not reach token_base and is not recycled
+			assert_eq!(Schelling::reward_pool(DEFAULT_TOPIC), 0);
+		});
+	}
+
+	#[test]
+	fn withdrawn_and_settled_non_revealers_forfeit_alike() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 5_000, ForfeitDestination::Base));
+			assert_ok!(Schelling::set_penalty_recycle_bps(root(), 5_000));
+			let base_balance = Token::balance_of(BASE);
+			commit(1, 10);
+			commit(2, 20);
+			commit(3, 30);
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+
+			// 2 withdraws after the deadline, 3 is left to the settlement
+			go_to_epoch_end();
+			assert_ok!(Schelling::withdraw(Origin::signed(2), DEFAULT_TOPIC));
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			assert_eq!(Token::balance_of(2), 1000 - DEPOSIT / 2);
+			assert_eq!(Token::balance_of(3), 1000 - DEPOSIT / 2);
+			assert_eq!(Token::balance_of(BASE), base_balance + DEPOSIT);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::NonRevealPenalized(2, DEPOSIT / 2))));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::NonRevealSlashed(3, DEPOSIT / 2))));
+			assert_eq!(Schelling::lifetime_penalties(), DEPOSIT);
+			// both forfeits reached token_base and half of each is recycled
+			assert_eq!(Schelling::reward_pool(DEFAULT_TOPIC), 2 * (DEPOSIT / 4));
+		});
 	}
 
-	impl consensus::Trait for Test {
-	    type Log = DigestItem;
-	    type SessionKey = UintAuthorityId;
-	    type InherentOfflineReport = ();
+	#[test]
+	fn commitments_of_a_forced_epoch_are_refunded_in_full() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			commit(1, 10);
+			go_to_epoch_end();
+			assert_ok!(Schelling::force_new_epoch(root(), DEFAULT_TOPIC));
+
+			assert_ok!(Schelling::withdraw(Origin::signed(1), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(1), 1000);
+		});
 	}
 
-	impl token::Trait for Test {
-	    type Event = ();
-	    type TokenBalance = u64;
+	#[test]
+	fn winner_history_is_paged() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			assert_ok!(Schelling::set_max_history_length(root(), 3));
+
+			let mut epochs = Vec::new();
+			for size in 2..=5 {
+				epochs.push(Schelling::epoch_start(DEFAULT_TOPIC));
+				let submissions: Vec<(u64, i64)> = (1..=size).map(|who| (who, who as i64 * 10)).collect();
+				run_epoch(&submissions);
+			}
+
+			// the first epoch is pruned, 3, 4 and 5 values reward 1, 2 and 3 of them
+			assert_eq!(Schelling::winner_history(), vec![(epochs[1], 1), (epochs[2], 2), (epochs[3], 3)]);
+			assert_eq!(Schelling::winner_history_page(0, 2), vec![(epochs[1], 1), (epochs[2], 2)]);
+			assert_eq!(Schelling::winner_history_page(1, 2), vec![(epochs[3], 3)]);
+			assert_eq!(Schelling::winner_history_page(2, 2), Vec::new());
+		});
 	}
-	  
-	impl Trait for Test {
-	    type Event = ();
+
+	#[test]
+	fn reputation_pages_cover_every_account_once() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			// 2, 3 and 4 are rewarded first, then 1, 3 and 5
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]);
+			run_epoch(&[(1, 20), (2, 10), (3, 30), (4, 50), (5, 40)]);
+			assert_eq!(Schelling::reputation(3), 2);
+
+			let mut pages = Vec::new();
+			for start in (0..6).step_by(2) {
+				pages.extend(Schelling::reputation_page(start, 2));
+			}
+			assert_eq!(pages, vec![(1, 1), (2, 1), (3, 2), (4, 1), (5, 1)]);
+			assert_eq!(Schelling::reputation_page(0, u32::max_value()), pages);
+			assert_eq!(Schelling::reputation_page(5, 2), Vec::new());
+		});
 	}
 
-	type schelling = Module<Test>;
-	type Token = token::Module<Test>;
+	#[test]
+	fn winners_are_paid_every_reward_asset() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			assert_noop!(Schelling::set_reward_bundles(root(), (0..9).map(|asset| (asset, 1)).collect()), "Too many reward assets");
+			assert_noop!(Schelling::set_reward_bundles(root(), vec![(1, 5), (2, 0)]), "Reward asset amounts must not be zero");
+			assert_noop!(Schelling::set_reward_bundles(root(), vec![(1, 5), (1, 7)]), "Reward assets must be distinct");
+			assert_ok!(Schelling::set_reward_bundles(root(), vec![(1, 5), (2, 7)]));
+			assert_ok!(Token::issue_asset(1, BASE, 100));
+			assert_ok!(Token::issue_asset(2, BASE, 20));
 
-	fn new_test_ext() -> runtime_io::TestExternalities<Blake2Hasher> {
-		system::GenesisConfig::<Test>::default().build_storage().unwrap().0.into()
+			let submissions = [(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)];
+			for &(who, value) in submissions.iter() {
+				commit(who, value);
+			}
+			go_to_reveal();
+			for &(who, value) in submissions.iter() {
+				assert_ok!(reveal(who, value));
+			}
+			go_to_epoch_end();
+
+			// 2, 3 and 4 are rewarded, 21 of the second asset are needed
+			assert_eq!(Schelling::send_rewards(root(), DEFAULT_TOPIC), Err("token_base can not fund the reward assets of the epoch"));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::SettlementAborted(DEFAULT_TOPIC, 21, 20))));
+			assert_eq!(Token::balance_of(2), 990);
+
+			assert_ok!(Token::issue_asset(2, BASE, 1));
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			for who in 2..=4 {
+				assert_eq!(Token::balance_of(who), 1100);
+				assert_eq!(Token::asset_balance_of((1, who)), 5);
+				assert_eq!(Token::asset_balance_of((2, who)), 7);
+			}
+			assert_eq!(Token::asset_balance_of((1, 1)), 0);
+			assert_eq!(Token::asset_balance_of((2, 5)), 0);
+			assert_eq!(Token::asset_balance_of((1, BASE)), 85);
+			assert_eq!(Token::asset_balance_of((2, BASE)), 0);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::AssetRewardPaid(3, 2, 7))));
+		});
 	}
 
 	#[test]
-	fn dummy_test() {
+	fn send_rewards_weight_grows_with_the_participants() {
 		with_externalities(&mut new_test_ext(), || {
-			assert_eq!(1, 1);
+			setup(&[1, 2, 3, 4]);
+			assert_eq!(Schelling::participant_count(DEFAULT_TOPIC), 0);
+			assert_eq!(Schelling::send_rewards_weight(0), SEND_REWARDS_BASE_WEIGHT);
+
+			for who in 1..=4 {
+				commit(who, who as i64 * 10);
+			}
+			assert_eq!(Schelling::participant_count(DEFAULT_TOPIC), 4);
+
+			go_to_reveal();
+			let mut weights = vec![Schelling::send_rewards_weight(Schelling::participant_count(DEFAULT_TOPIC))];
+			for who in 1..=4 {
+				assert_ok!(reveal(who, who as i64 * 10));
+				weights.push(Schelling::send_rewards_weight(Schelling::participant_count(DEFAULT_TOPIC)));
+			}
+			assert!(weights.windows(2).all(|pair| pair[0] < pair[1]));
+
+			// every revealed value is sorted and paid on top of its commitment being scanned
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 4);
+			assert_eq!(Schelling::participant_count(DEFAULT_TOPIC), 8);
+			// 8 participants take 4 comparisons each to sort
+			assert_eq!(weights[4], SEND_REWARDS_BASE_WEIGHT + 8 * 4 * COMPARE_WEIGHT + 8 * SEND_REWARDS_PARTICIPANT_WEIGHT);
+			assert!(Schelling::send_rewards_weight(300) > 300 * SEND_REWARDS_PARTICIPANT_WEIGHT);
+		});
+	}
+
+	#[test]
+	fn large_epochs_settle_every_submission() {
+		with_externalities(&mut new_test_ext(), || {
+			// keeps clear of the token_base account
+			let accounts: Vec<u64> = (101..=400).collect();
+			setup(&accounts);
+			let submissions: Vec<(u64, i64)> = accounts.iter().map(|&who| (who, who as i64 * 2)).collect();
+			run_epoch(&submissions);
+
+			// 300 values, the median between the values of accounts 250 and 251, indices 75 to 224 rewarded
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 501);
+			let rewarded = accounts.iter().filter(|who| Token::balance_of(*who) == 1100).count();
+			let penalized = accounts.iter().filter(|who| Token::balance_of(*who) == 999).count();
+			assert_eq!((rewarded, penalized), (150, 150));
+			assert_eq!(Token::balance_of(176), 1100);
+			assert_eq!(Token::balance_of(175), 999);
+			assert_eq!(Token::balance_of(325), 1100);
+			assert_eq!(Token::balance_of(326), 999);
+			assert!(accounts.iter().all(|who| !Schelling::has_revealed(DEFAULT_TOPIC, *who)));
+		});
+	}
+
+	#[test]
+	fn exact_median_ties_all_get_the_full_reward() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+
+			// the median 40 is reported at indices 1 to 6, the band only covers 2 to 5
+			run_epoch(&[(1, 10), (2, 40), (3, 40), (4, 40), (5, 40), (6, 40), (7, 40), (8, 90)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 40);
+			let balances: Vec<u64> = accounts.iter().map(|who| Token::balance_of(who)).collect();
+			assert_eq!(balances, vec![999, 1100, 1100, 1100, 1100, 1100, 1100, 999]);
+		});
+	}
+
+	#[test]
+	fn median_tolerance_extends_the_top_reward() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5];
+			setup(&accounts);
+			assert_ok!(Schelling::set_reward_tiers(root(), vec![
+				RewardTier { edge_bps: 2_000, multiplier_bps: 20_000 },
+				RewardTier { edge_bps: 6_000, multiplier_bps: 10_000 },
+			]));
+			assert_ok!(Schelling::set_median_tolerance(root(), 1));
+
+			// 29 and 31 are within the tolerance of the median 30 and share the central tier
+			run_epoch(&[(1, 10), (2, 29), (3, 30), (4, 31), (5, 50)]);
+			let balances: Vec<u64> = accounts.iter().map(|who| Token::balance_of(who)).collect();
+			assert_eq!(balances, vec![999, 1200, 1200, 1200, 999]);
+		});
+	}
+
+	#[test]
+	fn extended_reveal_window_accepts_late_reveals() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			for who in 1..=3 {
+				commit(who, who as i64 * 10);
+			}
+			assert_noop!(Schelling::extend_reveal_window(Origin::signed(1), DEFAULT_TOPIC, 10), "bad origin: expected to be a root origin");
+			assert_ok!(Schelling::extend_reveal_window(root(), DEFAULT_TOPIC, 10));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::RevealWindowExtended(1, 111))));
+
+			// past the original deadline of block 101
+			System::set_block_number(105);
+			assert_eq!(Schelling::blocks_until_resolution(), 6);
+			assert_noop!(Schelling::send_rewards(root(), DEFAULT_TOPIC), "It's not the time to send out the rewards yet");
+			for who in 1..=3 {
+				assert_ok!(reveal(who, who as i64 * 10));
+			}
+
+			System::set_block_number(111);
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+
+			// the next epoch keeps the configured rounds
+			assert_eq!(Schelling::reveal_extension(DEFAULT_TOPIC), 0);
+			assert_eq!(Schelling::blocks_until_resolution(), 100);
+		});
+	}
+
+	#[test]
+	fn settlement_emits_every_payout() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+
+			let payouts: Vec<TestEvent> = events().into_iter().filter(|event| match event {
+				TestEvent::schelling(RawEvent::DepositReturned(..)) => true,
+				TestEvent::schelling(RawEvent::RewardPaid(..)) => true,
+				TestEvent::schelling(RawEvent::PenaltyApplied(..)) => true,
+				_ => false,
+			}).collect();
+			assert_eq!(payouts, vec![
+				TestEvent::schelling(RawEvent::DepositReturned(1, 9)),
+				TestEvent::schelling(RawEvent::PenaltyApplied(1, 1, H256::zero())),
+				TestEvent::schelling(RawEvent::DepositReturned(2, DEPOSIT)),
+				TestEvent::schelling(RawEvent::RewardPaid(2, 100, H256::zero())),
+				TestEvent::schelling(RawEvent::DepositReturned(3, 9)),
+				TestEvent::schelling(RawEvent::PenaltyApplied(3, 1, H256::zero())),
+			]);
+		});
+	}
+
+	#[test]
+	fn emergency_halt_refunds_every_deposit() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_unrevealed_forfeit(root(), 5_000, ForfeitDestination::Base));
+			for who in 1..=3 {
+				commit(who, who as i64 * 10);
+			}
+			go_to_reveal();
+			assert_ok!(reveal(1, 10));
+
+			assert_noop!(Schelling::emergency_halt(Origin::signed(1)), "bad origin: expected to be a root origin");
+			assert_ok!(Schelling::emergency_halt(root()));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::EmergencyHalted(61))));
+			for who in 1..=3 {
+				assert_eq!(Token::balance_of(who), 1000);
+				assert_eq!(Token::locked_deposits(commitment(who, who as i64 * 10)), 0);
+				assert!(!<Messages<Test>>::exists(&DEFAULT_TOPIC, &who));
+			}
+			assert!(Schelling::committers(DEFAULT_TOPIC).is_empty());
+			assert!(Schelling::valid_messages(DEFAULT_TOPIC).is_empty());
+			assert!(!Schelling::has_revealed(DEFAULT_TOPIC, 1));
+			assert_eq!(Schelling::distinct_value_count(DEFAULT_TOPIC), 0);
+			assert!(!Schelling::epoch_active(DEFAULT_TOPIC));
+			assert_noop!(Schelling::emergency_halt(root()), "The oracle is already halted");
+
+			// nothing is settled while halted
+			go_to_epoch_end();
+			<Schelling as OnFinalize<u64>>::on_finalize(System::block_number());
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+		});
+	}
+
+	#[test]
+	fn halted_oracle_rejects_submissions_until_resumed() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2]);
+			commit(1, 10);
+			assert_ok!(Schelling::emergency_halt(root()));
+
+			assert_noop!(Schelling::submit_hash(Origin::signed(2), DEFAULT_TOPIC, commitment(2, 20i64), DEPOSIT), "The oracle is halted");
+			assert_noop!(reveal(1, 10), "The oracle is halted");
+			assert_noop!(Schelling::new_epoch(root(), DEFAULT_TOPIC), "The oracle is halted, use resume");
+			assert_noop!(Schelling::force_new_epoch(root(), DEFAULT_TOPIC), "The oracle is halted, use resume");
+			assert_noop!(Schelling::send_rewards(root(), DEFAULT_TOPIC), "The oracle is halted");
+			assert_noop!(Schelling::trigger_resolution(Origin::signed(2), DEFAULT_TOPIC), "The oracle is halted");
+			assert_noop!(Schelling::force_send_rewards(root(), DEFAULT_TOPIC), "The oracle is halted");
+
+			System::set_block_number(5);
+			assert_ok!(Schelling::resume(root()));
+			assert_noop!(Schelling::resume(root()), "The oracle is not halted");
+			assert!(!Schelling::halted());
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 5);
+
+			run_epoch(&[(1, 10), (2, 20)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 15);
+		});
+	}
+
+	#[test]
+	fn epoch_with_too_few_funders_is_invalidated() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_min_unique_funders(root(), 2));
+			for who in 1..=3 {
+				assert_ok!(Schelling::set_funder(root(), who, Some(9)));
+			}
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+
+			// all three accounts trace back to account 9
+			assert!(events().contains(&TestEvent::schelling(RawEvent::TooFewFunders(1))));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::EpochInvalidated(1, 30))));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+			for who in 1..=3 {
+				assert_eq!(Token::balance_of(who), 1000);
+			}
+
+			// an account without a funder counts as its own
+			assert_ok!(Schelling::set_funder(root(), 3, None));
+			run_epoch(&[(1, 10), (2, 20), (3, 30)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+		});
+	}
+
+	#[test]
+	fn last_sorted_values_are_the_resolution_input() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			run_epoch(&[(1, 40), (2, 10), (3, 50), (4, 20), (5, 30)]);
+
+			let sorted = Schelling::last_sorted_values();
+			assert_eq!(sorted, vec![10, 20, 30, 40, 50]);
+			assert_eq!(sorted.using_encoded(BlakeTwo256::hash), Schelling::last_dataset_hash());
+			assert_eq!(Schelling::resolve(&sorted), Some(Schelling::value(DEFAULT_TOPIC)));
+			assert_eq!(Schelling::last_sorted_values_from(1, 3), vec![20, 30, 40]);
+			assert_eq!(Schelling::last_sorted_values_from(4, 3), vec![50]);
+			assert_eq!(Schelling::last_sorted_values_from(5, 3), Vec::<i64>::new());
+		});
+	}
+
+	#[test]
+	fn fixed_point_feed_reports_negative_integers() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			// integers in degrees, e.g. a temperature below freezing
+			assert_ok!(Schelling::set_feed_config(root(), 0, b"C".to_vec()));
+			assert_ok!(Schelling::set_fixed_point(root(), true));
+			assert_ok!(Schelling::add_consumer(root(), 70));
+			let reveals = [(1, 15i128), (2, -20), (3, 0), (4, -5), (5, 7)];
+			for &(who, value) in reveals.iter() {
+				assert_ok!(Schelling::submit_hash(Origin::signed(who), DEFAULT_TOPIC, commitment(who, value), DEPOSIT));
+			}
+			go_to_reveal();
+
+			// the commitment binds the sign of the value
+			assert_noop!(Schelling::submit_fixed(Origin::signed(2), DEFAULT_TOPIC, 20, salt(2)), "The revealed value does not match the committed hash");
+			for &(who, value) in reveals.iter() {
+				assert_ok!(Schelling::submit_fixed(Origin::signed(who), DEFAULT_TOPIC, value, salt(who)));
+			}
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// sorted -20, -5, 0, 7, 15
+			assert_eq!(Schelling::fixed_value(), 0);
+			assert_eq!(Token::balance_of(4), 1100);
+			assert_eq!(Token::balance_of(3), 1100);
+			assert_eq!(Token::balance_of(5), 1100);
+			assert_eq!(Token::balance_of(2), 999);
+			assert_eq!(Token::balance_of(1), 999);
+			assert_eq!(Schelling::accuracy((DEFAULT_TOPIC, 2)), Accuracy { epochs: 1, average_distance: 20 });
+
+			// sorted -30, -10, 4, 9, halfway between -10 and 4
+			let reveals = [(1, 9i128), (2, -30), (3, 4), (4, -10)];
+			for &(who, value) in reveals.iter() {
+				assert_ok!(Schelling::submit_hash(Origin::signed(who), DEFAULT_TOPIC, commitment(who, value), DEPOSIT));
+			}
+			go_to_reveal();
+			for &(who, value) in reveals.iter() {
+				assert_ok!(Schelling::submit_fixed(Origin::signed(who), DEFAULT_TOPIC, value, salt(who)));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			assert_eq!(Schelling::fixed_value(), -3);
+			assert_eq!(Schelling::fixed_value_history(), vec![(1, 0), (102, -3)]);
+			assert_eq!(Schelling::last_sorted_fixed_values(), vec![-30, -10, 4, 9]);
+			assert_eq!(Schelling::last_dataset_hash(), vec![-30i128, -10, 4, 9].using_encoded(BlakeTwo256::hash));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::NewFixedValueSet(102, -3))));
+			assert!(Schelling::value_history().is_empty());
+			NOTIFIED_FIXED.with(|notified| assert_eq!(*notified.borrow(), vec![(70, 0), (70, -3)]));
+		});
+	}
+
+	#[test]
+	fn accurate_values_outside_the_band_get_the_consolation_reward() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			assert_ok!(Schelling::set_consolation_reward(root(), 5, 15));
+
+			// sorted 10, 20, 30, 40, 45, the band covers 20 to 40 around the median 30
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 45)]);
+
+			assert_eq!(Token::balance_of(3), 1100);
+			assert_eq!(Token::balance_of(5), 1005);
+			assert_eq!(Token::balance_of(1), 999);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::RewardPaid(5, 5, H256::zero()))));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::PenaltyApplied(1, 1, H256::zero()))));
+		});
+	}
+
+	#[test]
+	fn reveals_beyond_max_submissions_are_refused_and_withdrawable() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::set_max_submissions(root(), 3));
+			for who in 1..5 {
+				commit(who, 10 * who as i64);
+			}
+			go_to_reveal();
+
+			assert_noop!(Schelling::withdraw(Origin::signed(4), DEFAULT_TOPIC), "The reveal round is open, reveal the value or withdraw after the deadline");
+			for who in 1..4 {
+				assert_ok!(reveal(who, 10 * who as i64));
+			}
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 3);
+
+			assert_noop!(reveal(4, 40), "The maximum number of submissions is reached, please withdraw deposit");
+			assert_ok!(Schelling::withdraw(Origin::signed(4), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(4), 1000);
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+		});
+	}
+
+	#[test]
+	fn commitments_refused_by_max_submissions_are_refunded_on_settlement() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::set_max_submissions(root(), 3));
+			for who in 1..5 {
+				commit(who, 10 * who as i64);
+			}
+			go_to_reveal();
+			for who in 1..4 {
+				assert_ok!(reveal(who, 10 * who as i64));
+			}
+
+			// account 4 could not reveal and did not withdraw before the settlement
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(4), 1000);
+			assert!(!<Messages<Test>>::exists(&DEFAULT_TOPIC, &4));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::DepositReturned(4, DEPOSIT))));
+			assert!(!Schelling::last_slashed().contains(&4));
+		});
+	}
+
+	#[test]
+	fn resolution_is_overdue_once_the_resolution_block_passes_unsettled() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			for who in 1..4 {
+				commit(who, 10 * who as i64);
+			}
+			go_to_reveal();
+			for who in 1..4 {
+				assert_ok!(reveal(who, 10 * who as i64));
+			}
+			assert!(!Schelling::resolution_overdue());
+
+			System::set_block_number(101);
+			assert!(!Schelling::resolution_overdue());
+
+			go_to_epoch_end();
+			assert!(Schelling::resolution_overdue());
+
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert!(!Schelling::resolution_overdue());
+		});
+	}
+
+	#[test]
+	fn weighted_mode_resolves_the_deposit_weighted_median() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4]);
+			let submissions = [(1, 10, 10), (2, 20, 10), (3, 30, 50)];
+			let run = |submissions: &[(u64, i64, u64)]| {
+				for &(who, value, deposit) in submissions {
+					commit_with(who, value, deposit);
+				}
+				go_to_reveal();
+				for &(who, value, _) in submissions {
+					assert_ok!(reveal(who, value));
+				}
+				go_to_epoch_end();
+				assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			};
+
+			run(&submissions);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+
+			// 50 of the 70 deposited are on 30
+			assert_ok!(Schelling::set_weighted_mode(root(), true));
+			run(&submissions);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 30);
+
+			// 30 of the 60 deposited are on 10, halfway to 20
+			run(&[(1, 10, 30), (2, 20, 10), (3, 30, 10), (4, 40, 10)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 15);
+
+			assert_ok!(Schelling::set_weighted_mode(root(), false));
+			run(&[(1, 10, 30), (2, 20, 10), (3, 30, 10), (4, 40, 10)]);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+		});
+	}
+
+	#[test]
+	fn dust_of_penalized_deposits_is_swept_to_token_base() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			assert_ok!(Schelling::set_deposit_dust_threshold(root(), 5));
+			let token_base_balance = Token::balance_of(BASE);
+
+			commit_with(1, 10, 23);
+			commit(2, 20);
+			commit(3, 30);
+			go_to_reveal();
+			for who in 1..4 {
+				assert_ok!(reveal(who, 10 * who as i64));
+			}
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// 22 of 23 refunded down to 20, 9 of 10 down to 5
+			assert_eq!(Token::balance_of(1), 997);
+			assert_eq!(Token::balance_of(3), 995);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::PenaltyApplied(1, 3, H256::zero()))));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::PenaltyApplied(3, 5, H256::zero()))));
+			assert_eq!(Schelling::lifetime_penalties(), 8);
+			assert_eq!(Token::balance_of(BASE), token_base_balance + 8 - 100);
+			assert_eq!(Token::balance_of(1) + Token::balance_of(2) + Token::balance_of(3) + Token::balance_of(BASE), 3000 + token_base_balance);
+		});
+	}
+
+	#[test]
+	fn commit_reveal_settle_happy_path() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			let token_base_balance = Token::balance_of(BASE);
+			assert_eq!(Schelling::min_deposit(), DEPOSIT);
+
+			let submissions = [(1, 100), (2, 102), (3, 98), (4, 101), (5, 99), (6, 150), (7, 50), (8, 100)];
+			for &(who, value) in submissions.iter() {
+				assert_ok!(Schelling::submit_hash(Origin::signed(who), DEFAULT_TOPIC, commitment(who, value), DEPOSIT));
+				assert_eq!(Token::balance_of(who), 1000 - DEPOSIT);
+			}
+			assert_noop!(reveal(1, 100), "Hash submission round did not end yet");
+
+			go_to_reveal();
+			for &(who, value) in submissions.iter() {
+				assert_ok!(Schelling::submit_value(Origin::signed(who), DEFAULT_TOPIC, value, salt(who)));
+			}
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 8);
+			assert_noop!(Schelling::send_rewards(root(), DEFAULT_TOPIC), "It's not the time to send out the rewards yet");
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+
+			// sorted 50, 98, 99, 100, 100, 101, 102, 150, the middle half is rewarded
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 100);
+			for &who in [1, 4, 5, 8].iter() {
+				assert_eq!(Token::balance_of(who), 1100);
+			}
+			for &who in [2, 3, 6, 7].iter() {
+				assert_eq!(Token::balance_of(who), 999);
+			}
+			assert_eq!(Token::balance_of(BASE), token_base_balance - 400 + 4);
+			let total: u64 = accounts.iter().map(|who| Token::balance_of(*who)).sum();
+			assert_eq!(total + Token::balance_of(BASE), 8 * 1000 + token_base_balance);
+
+			// the next epoch starts clean
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), Schelling::value_set_block());
+			assert!(Schelling::valid_messages(DEFAULT_TOPIC).is_empty());
+			assert!(Schelling::committers(DEFAULT_TOPIC).is_empty());
+		});
+	}
+
+	#[test]
+	fn evidence_hash_is_kept_with_the_revealed_value() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3]);
+			commit(1, 10);
+			commit(2, 20);
+			commit(3, 30);
+			go_to_reveal();
+
+			let evidence = H256::from([7u8; 32]);
+			assert_ok!(Schelling::submit_value_with_evidence(Origin::signed(1), DEFAULT_TOPIC, 10, salt(1), evidence));
+			assert_ok!(reveal(2, 20));
+			assert_ok!(reveal(3, 30));
+
+			assert_eq!(Schelling::evidence_of(1, 1), Some(evidence));
+			assert_eq!(Schelling::evidence_of(2, 1), None);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::EvidenceAttached(1, evidence))));
+
+			// kept after settlement and reported with the payout
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::evidence_of(1, 1), Some(evidence));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::PenaltyApplied(1, 1, evidence))));
+		});
+	}
+
+	#[test]
+	fn underfunded_token_base_leaves_the_epoch_unsettled() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 3]);
+			// account 4 is funded without an allowance from token_base
+			assert_ok!(Token::transfer(Origin::signed(BASE), 4, 1000));
+			assert_ok!(Schelling::set_first_revealer_bonus(root(), 5));
+			let submissions = [(1, 10), (4, 20), (3, 30)];
+			for &(who, value) in submissions.iter() {
+				commit(who, value);
+			}
+			go_to_reveal();
+			for &(who, value) in submissions.iter() {
+				assert_ok!(reveal(who, value));
+			}
+			go_to_epoch_end();
+
+			// token_base is left with less than the first revealer bonus, the reward scaled down to 2 adds to it
+			let balance = Token::balance_of(BASE);
+			assert_ok!(Token::transfer(Origin::signed(BASE), 50, balance - 2));
+			assert_eq!(Schelling::send_rewards(root(), DEFAULT_TOPIC), Err("token_base can not fund the payouts of the epoch"));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::SettlementAborted(DEFAULT_TOPIC, 7, 2))));
+
+			// funded, but the reward of the winner is not approved
+			assert_ok!(Token::transfer(Origin::signed(50), BASE, 1000));
+			assert_eq!(Schelling::send_rewards(root(), DEFAULT_TOPIC), Err("token_base did not approve the payout of an account"));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::SettlementAborted(DEFAULT_TOPIC, 100, 0))));
+
+			// no deposit was unlocked and the epoch was not rolled over
+			for &(who, value) in submissions.iter() {
+				assert_eq!(Token::balance_of(who), 1000 - DEPOSIT);
+				assert_eq!(Token::locked_deposits(commitment(who, value)), DEPOSIT);
+			}
+			assert!(!events().iter().any(|event| match event {
+				TestEvent::schelling(RawEvent::DepositReturned(..)) => true,
+				TestEvent::schelling(RawEvent::InsufficientRewardFunds(..)) => true,
+				_ => false,
+			}));
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 3);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 1);
+			assert!(Schelling::epoch_active(DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+			assert_eq!(Schelling::accuracy((DEFAULT_TOPIC, 1)), Accuracy::default());
+
+			assert_ok!(Token::approve(Origin::signed(BASE), 4, 1000));
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Token::balance_of(4), 1100);
+			assert_eq!(Token::balance_of(1), 999 + 5);
+			assert_eq!(Token::balance_of(3), 999);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 102);
+		});
+	}
+
+	#[test]
+	fn fixed_pool_reward_is_split_among_the_winners() {
+		with_externalities(&mut new_test_ext(), || {
+			let accounts = [1, 2, 3, 4, 5, 6, 7, 8];
+			setup(&accounts);
+			assert_ok!(Schelling::set_fixed_pool_reward(root(), 120));
+			let paid = || events().into_iter().filter_map(|event| match event {
+				TestEvent::schelling(RawEvent::RewardPaid(_, reward, _)) => Some(reward),
+				_ => None,
+			}).collect::<Vec<u64>>();
+
+			// 2, 3 and 4 winners
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40)]);
+			assert_eq!(paid(), vec![60, 60]);
+
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]);
+			assert_eq!(paid()[2..], [40, 40, 40]);
+
+			let submissions: Vec<(u64, i64)> = accounts.iter().map(|who| (*who, *who as i64 * 10)).collect();
+			run_epoch(&submissions);
+			assert_eq!(paid()[5..], [30, 30, 30, 30]);
+
+			// back to the flat reward per winner
+			assert_ok!(Schelling::set_fixed_pool_reward(root(), 0));
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]);
+			assert_eq!(paid()[9..], [100, 100, 100]);
+		});
+	}
+
+	#[test]
+	fn weighted_fixed_pool_rewards_add_up_to_the_pool() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1, 2, 3, 4, 5]);
+			assert_ok!(Schelling::set_fixed_pool_reward(root(), 120));
+			assert_ok!(Schelling::set_weighted_rewards(root(), true));
+			run_epoch(&[(1, 10), (2, 20), (3, 30), (4, 40), (5, 50)]);
+
+			// weighted 20, 40 and 20 out of the even split of 40, scaled up to the pool
+			assert_eq!(Token::balance_of(2), 1030);
+			assert_eq!(Token::balance_of(3), 1060);
+			assert_eq!(Token::balance_of(4), 1030);
+		});
+	}
+
+	#[test]
+	fn topics_resolve_independently() {
+		with_externalities(&mut new_test_ext(), || {
+			const PAIR: TopicId = 7;
+			setup(&[1, 2, 3]);
+			assert_noop!(Schelling::create_topic(Origin::signed(1), PAIR), "bad origin: expected to be a root origin");
+			assert_ok!(Schelling::create_topic(root(), PAIR));
+			assert_noop!(Schelling::create_topic(root(), PAIR), "The topic already exists");
+			assert_noop!(Schelling::create_topic(root(), DEFAULT_TOPIC), "The topic already exists");
+			assert_eq!(Schelling::epoch_start(PAIR), 1);
+
+			// the same accounts take part in both topics
+			for who in 1..=3 {
+				commit(who, who as i64 * 10);
+				assert_ok!(Schelling::submit_hash(Origin::signed(who), PAIR, commitment(who, who as i64 * 1000), DEPOSIT));
+			}
+			assert_noop!(Schelling::submit_hash(Origin::signed(1), 8, commitment(1, 10i64), DEPOSIT), "The topic does not exist");
+			go_to_reveal();
+			for who in 1..=3 {
+				assert_ok!(reveal(who, who as i64 * 10));
+				assert_ok!(Schelling::submit_value(Origin::signed(who), PAIR, who as i64 * 1000, salt(who)));
+			}
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 3);
+			assert_eq!(Schelling::valid_messages(PAIR).len(), 3);
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), PAIR));
+			assert_eq!(Schelling::value(PAIR), 2000);
+			assert!(events().contains(&TestEvent::schelling(RawEvent::TopicValueSet(PAIR, 1, 2000))));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 0);
+			assert_eq!(Schelling::valid_messages(DEFAULT_TOPIC).len(), 3);
+
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 20);
+			assert_eq!(Schelling::value(PAIR), 2000);
+			// the value history follows the default topic
+			assert_eq!(Schelling::value_history(), vec![(1, 20)]);
+			// the median account was rewarded in both topics
+			assert_eq!(Token::balance_of(2), 1200);
+		});
+	}
+
+	#[test]
+	fn topics_run_their_own_epochs() {
+		with_externalities(&mut new_test_ext(), || {
+			setup(&[1]);
+			System::set_block_number(30);
+			assert_ok!(Schelling::create_topic(root(), 7));
+			assert!(events().contains(&TestEvent::schelling(RawEvent::TopicEpochStarted(7, 30))));
+
+			// the reveal round of the default topic closes first
+			System::set_block_number(101);
+			<Schelling as OnFinalize<u64>>::on_finalize(101);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 101);
+			assert_eq!(Schelling::epoch_start(7), 30);
+			assert_noop!(Schelling::send_rewards(root(), 7), "It's not the time to send out the rewards yet");
+
+			System::set_block_number(130);
+			<Schelling as OnFinalize<u64>>::on_finalize(130);
+			assert_eq!(Schelling::epoch_start(7), 130);
+			assert_eq!(Schelling::epoch_start(DEFAULT_TOPIC), 101);
+		});
+	}
+
+	#[test]
+	fn overlapping_topics_keep_their_own_state() {
+		with_externalities(&mut new_test_ext(), || {
+			const PAIR: TopicId = 7;
+			setup(&[1, 2, 3, 4]);
+			assert_ok!(Schelling::create_topic(root(), PAIR));
+			assert_ok!(Schelling::set_penalty_recycle_bps(root(), 10_000));
+			assert_ok!(Schelling::set_reward_escrow(root(), true, 50));
+
+			// durations set during the epoch of one topic wait for its next epoch only
+			assert_ok!(Schelling::set_round_durations(root(), PAIR, 10, 20));
+			assert_eq!(Schelling::pending_round_durations(PAIR), Some((10, 20)));
+			assert_eq!(Schelling::pending_round_durations(DEFAULT_TOPIC), None);
+
+			// the same accounts take part in both topics, the outer values of the default topic are penalized
+			for who in 1..=4 {
+				commit(who, who as i64 * 10);
+				assert_ok!(Schelling::submit_hash(Origin::signed(who), PAIR, commitment(who, 500i64), DEPOSIT));
+			}
+			go_to_reveal();
+			for who in 1..=4 {
+				assert_ok!(reveal(who, who as i64 * 10));
+				assert_ok!(Schelling::submit_value(Origin::signed(who), PAIR, 500, salt(who)));
+			}
+
+			go_to_epoch_end();
+			assert_ok!(Schelling::send_rewards(root(), PAIR));
+			assert_ok!(Schelling::send_rewards(root(), DEFAULT_TOPIC));
+			assert_eq!(Schelling::value(PAIR), 500);
+			assert_eq!(Schelling::value(DEFAULT_TOPIC), 25);
+
+			// the penalties of the default topic only fund its own pool
+			assert_eq!(Schelling::reward_pool(DEFAULT_TOPIC), 2);
+			assert_eq!(Schelling::reward_pool(PAIR), 0);
+			assert_eq!(Schelling::accuracy((DEFAULT_TOPIC, 1)), Accuracy { epochs: 1, average_distance: 15 });
+			assert_eq!(Schelling::accuracy((PAIR, 1)), Accuracy { epochs: 1, average_distance: 0 });
+			assert_eq!(Schelling::hash_submission_blocks(PAIR), 10);
+			assert_eq!(Schelling::value_submission_blocks(PAIR), 20);
+			assert_eq!(Schelling::hash_submission_blocks(DEFAULT_TOPIC), 50);
+			assert_eq!(Schelling::epoch_index(PAIR), 2);
+
+			// both epochs started on block 1, their escrowed rewards are claimed separately
+			assert_eq!(Schelling::claimable_reward((PAIR, 1, 1)), 100);
+			assert_eq!(Schelling::claimable_reward((DEFAULT_TOPIC, 1, 1)), 0);
+			assert_eq!(Schelling::claimable_reward((DEFAULT_TOPIC, 2, 1)), 100);
+			assert_eq!(Schelling::claim_deadline((PAIR, 1)), 152);
+			assert_ok!(Schelling::claim_reward(Origin::signed(2), PAIR, 1));
+			assert_eq!(Token::balance_of(2), 1100);
+			assert_noop!(Schelling::claim_reward(Origin::signed(2), PAIR, 1), "There is no reward to claim for this epoch");
+			assert_eq!(Schelling::claimable_reward((DEFAULT_TOPIC, 2, 1)), 100);
+
+			// calls of a topic only reach its own commitments
+			System::set_block_number(110);
+			commit(1, 10);
+			assert_noop!(Schelling::increase_deposit(Origin::signed(1), PAIR, DEPOSIT), "Message hash was not submitted");
+			assert_ok!(Schelling::increase_deposit(Origin::signed(1), DEFAULT_TOPIC, DEPOSIT));
 		});
 	}
 }
@@ -9,6 +9,10 @@ use support::{dispatch::Result, StorageMap, Parameter, StorageValue, decl_storag
 use system::{self, ensure_signed};
 use runtime_primitives::traits::{CheckedSub, CheckedAdd, Member, SimpleArithmetic, As};
 
+// identifies a token held besides the main one, e.g. a partner token paid out with the rewards
+// not part of standard ERC20 interface
+pub type AssetId = u32;
+
 // trait for this module
 // contains type definitions
 pub trait Trait: system::Trait {
@@ -65,6 +69,13 @@ decl_module! {
           Self::deposit_event(RawEvent::Approval(from.clone(), to.clone(), value));
           Self::_transfer(from, to, value)
       }
+
+      // transfer tokens of an asset besides the main one
+      // not part of standard ERC20 interface
+      pub fn transfer_asset(origin, asset: AssetId, to: T::AccountId, #[compact] value: T::TokenBalance) -> Result {
+          let sender = ensure_signed(origin)?;
+          Self::move_asset(asset, sender, to, value)
+      }
   }
 }
 
@@ -85,6 +96,9 @@ decl_storage! {
     // maps a listing hash with the total tokensface
     // TCR specific; not part of standard ERC20 interface
     LockedDeposits get(locked_deposits): map T::Hash => T::TokenBalance;
+    // mapping of the balances of the assets besides the main token to accounts
+    // not part of standard ERC20 interface
+    AssetBalanceOf get(asset_balance_of): map (AssetId, T::AccountId) => T::TokenBalance;
   }
 }
 
@@ -97,6 +111,9 @@ decl_event!(
         // event when an approval is made
         // owner, spender, value
         Approval(AccountId, AccountId, TokenBalance),
+        // event for transfer of an asset besides the main token
+        // asset, from, to, value
+        AssetTransfer(AssetId, AccountId, AccountId, TokenBalance),
     }
 );
 
@@ -121,6 +138,36 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    // create tokens of an asset besides the main one in an account
+    // not part of standard ERC20 interface
+    pub fn issue_asset(asset: AssetId, to: T::AccountId, value: T::TokenBalance) -> Result {
+        let balance = Self::asset_balance_of((asset, to.clone()));
+        let updated_balance = balance.checked_add(&value).ok_or("overflow in calculating balance")?;
+
+        <AssetBalanceOf<T>>::insert((asset, to), updated_balance);
+
+        Ok(())
+    }
+
+    // move tokens of an asset besides the main one between accounts, e.g. to pay out rewards
+    // not part of standard ERC20 interface
+    pub fn move_asset(asset: AssetId, from: T::AccountId, to: T::AccountId, value: T::TokenBalance) -> Result {
+        let sender_balance = Self::asset_balance_of((asset, from.clone()));
+        ensure!(sender_balance >= value, "Not enough balance.");
+        let updated_from_balance = sender_balance.checked_sub(&value).ok_or("overflow in calculating balance")?;
+        let receiver_balance = Self::asset_balance_of((asset, to.clone()));
+        let updated_to_balance = receiver_balance.checked_add(&value).ok_or("overflow in calculating balance")?;
+
+        // reduce sender's balance
+        <AssetBalanceOf<T>>::insert((asset, from.clone()), updated_from_balance);
+
+        // increase receiver's balance
+        <AssetBalanceOf<T>>::insert((asset, to.clone()), updated_to_balance);
+
+        Self::deposit_event(RawEvent::AssetTransfer(asset, from, to, value));
+        Ok(())
+    }
+
     // lock user deposits for curation actions
     // TCR specific; not part of standard ERC20 interface
     pub fn lock(from: T::AccountId, value: T::TokenBalance, listing_hash: T::Hash) -> Result {
@@ -158,6 +205,39 @@ impl<T: Trait> Module<T> {
         Ok(())
     }
 
+    // move locked deposits from one listing to another without returning them to a balance
+    // TCR specific; not part of standard ERC20 interface
+    pub fn relock(value: T::TokenBalance, from_listing: T::Hash, to_listing: T::Hash) -> Result {
+        let from_deposit = Self::locked_deposits(from_listing);
+        let updated_from_deposit = from_deposit.checked_sub(&value).ok_or("overflow in calculating deposit")?;
+        let to_deposit = Self::locked_deposits(to_listing);
+        let updated_to_deposit = to_deposit.checked_add(&value).ok_or("overflow in calculating deposit")?;
+
+        // decrease the source deposit
+        <LockedDeposits<T>>::insert(from_listing, updated_from_deposit);
+
+        // add to the destination deposit
+        <LockedDeposits<T>>::insert(to_listing, updated_to_deposit);
+
+        Ok(())
+    }
+
+    // destroy part of a locked deposit, reducing the total supply
+    // TCR specific; not part of standard ERC20 interface
+    pub fn burn(value: T::TokenBalance, listing_hash: T::Hash) -> Result {
+        let deposit = Self::locked_deposits(listing_hash);
+        let updated_deposit = deposit.checked_sub(&value).ok_or("overflow in calculating deposit")?;
+        let updated_supply = Self::total_supply().checked_sub(&value).ok_or("overflow in calculating total supply")?;
+
+        // decrease from locked deposits
+        <LockedDeposits<T>>::insert(listing_hash, updated_deposit);
+
+        // decrease the total supply
+        <TotalSupply<T>>::put(updated_supply);
+
+        Ok(())
+    }
+
     // internal transfer function for ERC20 interface
     fn _transfer(
         from: T::AccountId,
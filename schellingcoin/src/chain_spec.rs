@@ -1,7 +1,7 @@
 use primitives::{ed25519, sr25519, Pair};
 use schelling_runtime::{
 	AccountId, GenesisConfig, ConsensusConfig, TimestampConfig, BalancesConfig,
-	SudoConfig, IndicesConfig,
+	SudoConfig, IndicesConfig, SchellingModuleConfig,
 };
 use substrate_service;
 
@@ -113,7 +113,12 @@ fn testnet_genesis(initial_authorities: Vec<AuthorityId>, endowed_accounts: Vec<
 			vesting: vec![],
 		}),
 		sudo: Some(SudoConfig {
-			key: root_key,
+			key: root_key.clone(),
+		}),
+		schelling_module: Some(SchellingModuleConfig {
+			token_base: root_key,
+			min_deposit: 1,
+			epoch_start: 0,
 		}),
 	}
 }